@@ -5,9 +5,21 @@
 use fix_path_env::fix;
 
 fn main() {
-  if let Err(e) = fix() {
-    println!("{}", e);
-  } else {
-    println!("PATH: {}", std::env::var("PATH").unwrap());
+  match fix() {
+    Ok(report) => {
+      println!(
+        "ran {} in {:?}",
+        report.shell().display(),
+        report.duration()
+      );
+      for change in report.changed() {
+        println!(
+          "changed {:?}: {:?} -> {:?}",
+          change.name, change.old, change.new
+        );
+      }
+      println!("PATH: {}", std::env::var("PATH").unwrap());
+    }
+    Err(e) => println!("{}", e),
   }
 }
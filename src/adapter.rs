@@ -0,0 +1,296 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  fmt,
+  path::{Path, PathBuf},
+  process::Command,
+};
+
+/// The startup files of POSIX shells, relative to the home directory.
+const HOME_FILES: &[&str] = &[
+  ".profile",
+  ".bash_profile",
+  ".bash_login",
+  ".bashrc",
+  ".zshenv",
+  ".zprofile",
+  ".zshrc",
+  ".zlogin",
+];
+
+/// The system-wide startup files of POSIX shells.
+const SYSTEM_FILES: &[&str] = &[
+  "/etc/profile",
+  "/etc/profile.d",
+  "/etc/environment",
+  "/etc/bashrc",
+  "/etc/bash.bashrc",
+  "/etc/zshenv",
+  "/etc/zprofile",
+  "/etc/zshrc",
+  "/etc/zlogin",
+  "/etc/zsh/zshenv",
+  "/etc/zsh/zprofile",
+  "/etc/zsh/zshrc",
+  "/etc/zsh/zlogin",
+  "/etc/paths",
+  "/etc/paths.d",
+];
+
+/// Describes how a shell is probed for its environment.
+///
+/// The crate ships adapters for POSIX shells (sh, bash, zsh, dash...), fish, nu, csh/tcsh,
+/// PowerShell, xonsh and elvish. Other shells can be supported by registering an adapter
+/// with [`crate::Fixer::adapter`], which is tried before the built-in ones.
+///
+/// ```no_run
+/// use std::{ffi::OsString, path::Path};
+/// use fix_path_env::{Delimiter, Fixer, ShellAdapter};
+///
+/// #[derive(Debug)]
+/// struct Oil;
+///
+/// impl ShellAdapter for Oil {
+///   fn name(&self) -> &str {
+///     "oil"
+///   }
+///
+///   fn matches(&self, shell: &Path) -> bool {
+///     shell.ends_with("osh")
+///   }
+///
+///   fn args(&self, _shell: &Path) -> Vec<OsString> {
+///     vec!["--login".into(), "-c".into()]
+///   }
+///
+///   fn script(&self, delimiter: &Delimiter) -> String {
+///     let (open_prefix, open_suffix) = delimiter.open_halves();
+///     let (close_prefix, close_suffix) = delimiter.close_halves();
+///     format!("printf '%s%s' {open_prefix} {open_suffix}; env; printf '%s%s' {close_prefix} {close_suffix}")
+///   }
+///
+///   fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+///     let dump = std::str::from_utf8(dump).map_err(|e| e.to_string())?;
+///     Ok(
+///       dump
+///         .lines()
+///         .filter_map(|line| line.split_once('='))
+///         .map(|(k, v)| (k.into(), v.into()))
+///         .collect(),
+///     )
+///   }
+/// }
+///
+/// Fixer::new().shell("/usr/local/bin/osh").adapter(Oil).run().unwrap();
+/// ```
+pub trait ShellAdapter: fmt::Debug + Send + Sync {
+  /// A name identifying this adapter in the [cache](crate::Fixer::cache) key.
+  fn name(&self) -> &str;
+
+  /// Whether this adapter knows how to probe the given shell binary.
+  fn matches(&self, shell: &Path) -> bool;
+
+  /// The command running the shell, without arguments.
+  ///
+  /// Defaults to running the binary itself.
+  fn command(&self, shell: &Path) -> Command {
+    Command::new(shell)
+  }
+
+  /// The arguments starting a login shell that runs the probe script passed after them,
+  /// replaced by [`crate::Fixer::args`] when set.
+  fn args(&self, shell: &Path) -> Vec<OsString>;
+
+  /// Variables set on the shell process, before the ones added with [`crate::Fixer::env`].
+  fn envs(&self, _shell: &Path) -> Vec<(OsString, OsString)> {
+    Vec::new()
+  }
+
+  /// The script printing the environment between the markers of `delimiter`.
+  ///
+  /// Print each marker in [halves](Delimiter::open_halves) so it never appears verbatim
+  /// in the command line, where a shell echoing its input could print it too early.
+  fn script(&self, delimiter: &Delimiter) -> String;
+
+  /// Parses what the script printed between the markers into `(name, value)` pairs.
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String>;
+
+  /// The files and directories read by the shell on startup, whose changes invalidate
+  /// the [cache](crate::Fixer::cache).
+  ///
+  /// Defaults to the startup files of sh, bash and zsh, see [`posix_startup_files`].
+  fn startup_files(&self, home: &Path) -> Vec<PathBuf> {
+    posix_startup_files(home)
+  }
+}
+
+/// The startup files of sh, bash and zsh in `home`, `$ZDOTDIR` and `/etc`.
+pub fn posix_startup_files(home: &Path) -> Vec<PathBuf> {
+  let mut files = HOME_FILES.iter().map(|f| home.join(f)).collect::<Vec<_>>();
+  if let Some(zdotdir) = std::env::var_os("ZDOTDIR").map(PathBuf::from) {
+    files.extend([".zshenv", ".zprofile", ".zshrc", ".zlogin"].map(|f| zdotdir.join(f)));
+  }
+  files.extend(SYSTEM_FILES.iter().map(PathBuf::from));
+  files
+}
+
+/// The markers printed around the environment dump.
+///
+/// They contain a random nonce so no variable value or shell output can forge them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delimiter {
+  open: String,
+  close: String,
+}
+
+impl Delimiter {
+  #[cfg(not(windows))]
+  pub(crate) fn random() -> Self {
+    let nonce = nonce()
+      .iter()
+      .map(|b| format!("{b:02x}"))
+      .collect::<String>();
+    Self {
+      open: format!("_SHELL_ENV_BEGIN_{nonce}_"),
+      close: format!("_SHELL_ENV_END_{nonce}_"),
+    }
+  }
+
+  /// The marker printed before the environment.
+  pub fn open(&self) -> &str {
+    &self.open
+  }
+
+  /// The marker printed after the environment.
+  pub fn close(&self) -> &str {
+    &self.close
+  }
+
+  /// The opening marker split in two halves, so scripts can print it without it appearing
+  /// verbatim in the command line.
+  pub fn open_halves(&self) -> (&str, &str) {
+    self.open.split_at(self.open.len() / 2)
+  }
+
+  /// The closing marker split in two halves, see [`Delimiter::open_halves`].
+  pub fn close_halves(&self) -> (&str, &str) {
+    self.close.split_at(self.close.len() / 2)
+  }
+
+  /// Returns the dump between the opening and closing markers,
+  /// making sure each one was printed exactly once and in order.
+  #[cfg(not(windows))]
+  pub(crate) fn extract<'a>(&self, stdout: &'a [u8]) -> Result<&'a [u8], crate::MarkerError> {
+    use crate::MarkerError;
+
+    let open = self.open.as_bytes();
+    let close = self.close.as_bytes();
+    let start = match find_all(stdout, open).as_slice() {
+      [] => return Err(MarkerError::MissingOpening),
+      [start] => start + open.len(),
+      _ => return Err(MarkerError::DuplicateOpening),
+    };
+    let end = match find_all(stdout, close).as_slice() {
+      [end] if *end >= start => *end,
+      [_] | [] => return Err(MarkerError::MissingClosing),
+      _ => return Err(MarkerError::DuplicateClosing),
+    };
+    Ok(&stdout[start..end])
+  }
+}
+
+#[cfg(not(windows))]
+fn nonce() -> [u8; 16] {
+  use std::io::Read;
+
+  let mut nonce = [0; 16];
+  if std::fs::File::open("/dev/urandom")
+    .and_then(|mut f| f.read_exact(&mut nonce))
+    .is_err()
+  {
+    // `RandomState` is seeded from the OS random source
+    use std::hash::{BuildHasher, Hasher};
+    for chunk in nonce.chunks_mut(8) {
+      let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+      hasher.write_u128(
+        std::time::SystemTime::now()
+          .duration_since(std::time::UNIX_EPOCH)
+          .unwrap_or_default()
+          .as_nanos(),
+      );
+      chunk.copy_from_slice(&hasher.finish().to_ne_bytes());
+    }
+  }
+  nonce
+}
+
+#[cfg(not(windows))]
+fn find_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+  haystack
+    .windows(needle.len())
+    .enumerate()
+    .filter(|(_, w)| *w == needle)
+    .map(|(i, _)| i)
+    .collect()
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+  use super::*;
+  use crate::MarkerError;
+
+  fn delimiter() -> Delimiter {
+    Delimiter {
+      open: "<open>".into(),
+      close: "<close>".into(),
+    }
+  }
+
+  #[test]
+  fn extract() {
+    let delimiter = delimiter();
+    assert_eq!(
+      delimiter.extract(b"motd\n<open>A=1\0<close>bye"),
+      Ok(&b"A=1\0"[..])
+    );
+    assert_eq!(delimiter.extract(b"<open><close>"), Ok(&b""[..]));
+  }
+
+  #[test]
+  fn extract_missing() {
+    let delimiter = delimiter();
+    assert_eq!(
+      delimiter.extract(b"A=1\0<close>"),
+      Err(MarkerError::MissingOpening)
+    );
+    assert_eq!(
+      delimiter.extract(b"<open>A=1\0"),
+      Err(MarkerError::MissingClosing)
+    );
+    assert_eq!(delimiter.extract(b""), Err(MarkerError::MissingOpening));
+  }
+
+  #[test]
+  fn extract_duplicate() {
+    let delimiter = delimiter();
+    assert_eq!(
+      delimiter.extract(b"<open><open>A=1\0<close>"),
+      Err(MarkerError::DuplicateOpening)
+    );
+    assert_eq!(
+      delimiter.extract(b"<open>A=1\0<close><close>"),
+      Err(MarkerError::DuplicateClosing)
+    );
+  }
+
+  #[test]
+  fn extract_closing_first() {
+    assert_eq!(
+      delimiter().extract(b"<close>A=1\0<open>"),
+      Err(MarkerError::MissingClosing)
+    );
+  }
+}
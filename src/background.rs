@@ -0,0 +1,87 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  sync::{Mutex, OnceLock},
+  thread::JoinHandle,
+};
+
+use crate::{Error, FixReport, VarChange};
+
+/// A shell probe running on a background thread, see [`crate::Fixer::spawn`].
+///
+/// The resolved variables are only applied to the current process when [`FixHandle::apply`] is called,
+/// and at most once no matter how many times or from how many threads it is called.
+/// Dropping the handle lets the thread finish without applying anything.
+#[derive(Debug)]
+pub struct FixHandle {
+  thread: Mutex<Option<JoinHandle<Result<FixReport, Error>>>>,
+  result: OnceLock<Result<FixReport, Error>>,
+  applied: OnceLock<Vec<VarChange>>,
+}
+
+impl FixHandle {
+  pub(crate) fn new(thread: JoinHandle<Result<FixReport, Error>>) -> Self {
+    Self {
+      thread: Mutex::new(Some(thread)),
+      result: OnceLock::new(),
+      applied: OnceLock::new(),
+    }
+  }
+
+  /// Blocks until the shell environment is resolved, without applying it.
+  ///
+  /// If the probe thread panicked, every call returns an [`Error::Shell`] with the panic message.
+  pub fn wait(&self) -> Result<&FixReport, &Error> {
+    self
+      .result
+      .get_or_init(|| {
+        let thread = self
+          .thread
+          .lock()
+          .unwrap_or_else(|e| e.into_inner())
+          .take()
+          .expect("the probe thread is only joined once");
+        thread.join().unwrap_or_else(|panic| {
+          let message = panic
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+          Err(Error::Shell(std::io::Error::other(format!(
+            "the probe thread panicked: {message}"
+          ))))
+        })
+      })
+      .as_ref()
+  }
+
+  /// Returns the result if the shell environment is already resolved, without blocking.
+  pub fn try_get(&self) -> Option<Result<&FixReport, &Error>> {
+    if let Some(result) = self.result.get() {
+      return Some(result.as_ref());
+    }
+    let finished = match self
+      .thread
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .as_ref()
+    {
+      Some(thread) => thread.is_finished(),
+      // another thread is joining it
+      None => false,
+    };
+    finished.then(|| self.wait())
+  }
+
+  /// Blocks until the shell environment is resolved and applies it to the current process,
+  /// merging each variable according to its [`crate::MergePolicy`].
+  ///
+  /// The process environment is only modified by the first call;
+  /// every call returns the changes made by that first one.
+  pub fn apply(&self) -> Result<&[VarChange], &Error> {
+    let report = self.wait()?;
+    Ok(self.applied.get_or_init(|| report.env().apply()))
+  }
+}
@@ -0,0 +1,142 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  collections::hash_map::DefaultHasher,
+  ffi::{OsStr, OsString},
+  fs,
+  hash::{Hash, Hasher},
+  io::{self, Write},
+  os::unix::{
+    ffi::OsStrExt,
+    fs::{DirBuilderExt, OpenOptionsExt},
+  },
+  path::{Path, PathBuf},
+  time::{Duration, SystemTime},
+};
+
+use crate::ShellAdapter;
+
+/// The on-disk cache of resolved environments.
+///
+/// Each entry is named `<config>-<files>`: a hash of the [`crate::Fixer`] configuration
+/// and a hash of the shell binary and startup files metadata,
+/// so editing a dotfile invalidates the entry.
+#[derive(Debug, Clone)]
+pub(crate) struct Cache {
+  dir: PathBuf,
+  config: u64,
+}
+
+impl Cache {
+  /// `config` is anything that changes the resolved environment besides the startup files.
+  pub fn new(dir: PathBuf, config: impl Hash) -> Self {
+    let mut hasher = DefaultHasher::new();
+    config.hash(&mut hasher);
+    Self {
+      dir,
+      config: hasher.finish(),
+    }
+  }
+
+  /// Loads the entry for the given shell, returning it along with its age.
+  pub fn load(
+    &self,
+    shell: &Path,
+    adapter: &dyn ShellAdapter,
+  ) -> Option<(Vec<(OsString, OsString)>, Duration)> {
+    let path = self.entry(shell, adapter);
+    let age = fs::metadata(&path)
+      .and_then(|m| m.modified())
+      .ok()
+      .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+    let records = fs::read(path).ok()?;
+    Some((crate::parse::parse_env(&records), age))
+  }
+
+  /// Stores the entry for the given shell, removing the outdated ones of the same configuration.
+  pub fn store<'a>(
+    &self,
+    shell: &Path,
+    adapter: &dyn ShellAdapter,
+    env: impl Iterator<Item = (&'a OsStr, &'a OsStr)>,
+  ) -> io::Result<()> {
+    fs::DirBuilder::new()
+      .recursive(true)
+      .mode(0o700)
+      .create(&self.dir)?;
+
+    let path = self.entry(shell, adapter);
+    let prefix = format!("{:016x}-", self.config);
+    for entry in fs::read_dir(&self.dir)?.flatten() {
+      if entry.file_name().to_string_lossy().starts_with(&prefix) && entry.path() != path {
+        let _ = fs::remove_file(entry.path());
+      }
+    }
+
+    // the environment may contain secrets, so only the user can read it.
+    // A leftover file (or a symlink planted in its place) is removed rather than followed.
+    let tmp = path.with_extension("tmp");
+    let _ = fs::remove_file(&tmp);
+    let mut file = fs::OpenOptions::new()
+      .write(true)
+      .create_new(true)
+      .custom_flags(libc::O_NOFOLLOW)
+      .mode(0o600)
+      .open(&tmp)?;
+    for (key, value) in env {
+      file.write_all(key.as_bytes())?;
+      file.write_all(b"=")?;
+      file.write_all(value.as_bytes())?;
+      file.write_all(b"\0")?;
+    }
+    file.sync_all()?;
+    fs::rename(tmp, path)
+  }
+
+  fn entry(&self, shell: &Path, adapter: &dyn ShellAdapter) -> PathBuf {
+    self.dir.join(format!(
+      "{:016x}-{:016x}",
+      self.config,
+      files_hash(shell, adapter)
+    ))
+  }
+}
+
+/// The default cache directory: `$XDG_CACHE_HOME/fix-path-env`, `~/Library/Caches/fix-path-env` on macOS
+/// or `~/.cache/fix-path-env`.
+pub(crate) fn default_dir() -> Option<PathBuf> {
+  let base = std::env::var_os("XDG_CACHE_HOME")
+    .map(PathBuf::from)
+    .filter(|p| p.is_absolute())
+    .or_else(|| {
+      home::home_dir().map(|home| {
+        if cfg!(target_os = "macos") {
+          home.join("Library/Caches")
+        } else {
+          home.join(".cache")
+        }
+      })
+    })?;
+  Some(base.join("fix-path-env"))
+}
+
+/// Hashes the metadata of the shell binary and every [startup file](ShellAdapter::startup_files).
+// `DefaultHasher` is not stable across Rust releases, which only causes a cache miss.
+fn files_hash(shell: &Path, adapter: &dyn ShellAdapter) -> u64 {
+  let mut files = vec![shell.to_path_buf()];
+  if let Some(home) = home::home_dir() {
+    files.extend(adapter.startup_files(&home));
+  }
+
+  let mut hasher = DefaultHasher::new();
+  for file in files {
+    file.hash(&mut hasher);
+    if let Ok(metadata) = fs::metadata(&file) {
+      metadata.len().hash(&mut hasher);
+      metadata.modified().ok().hash(&mut hasher);
+    }
+  }
+  hasher.finish()
+}
@@ -0,0 +1,48 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{ffi::OsStr, process::Command};
+
+use crate::ShellEnv;
+
+/// Applies a [`ShellEnv`] to a child process instead of the current one.
+///
+/// ```no_run
+/// use fix_path_env::CommandExt;
+///
+/// let env = fix_path_env::shell_env(&["PATH"]).unwrap();
+/// std::process::Command::new("node")
+///   .with_shell_env(&env)
+///   .status()
+///   .unwrap();
+/// ```
+pub trait CommandExt {
+  /// Sets the variables on the command, merging them according to their [`crate::MergePolicy`]
+  /// with the value the command would otherwise get.
+  ///
+  /// [`Command`] does not tell whether [`Command::env_clear`] was called, so a variable
+  /// the command does not set itself is always merged with its value in the current process,
+  /// even after `env_clear`. Set it on the command (or remove it with [`Command::env_remove`])
+  /// before calling this to control what it is merged with.
+  fn with_shell_env(&mut self, env: &ShellEnv) -> &mut Self;
+}
+
+impl CommandExt for Command {
+  fn with_shell_env(&mut self, env: &ShellEnv) -> &mut Self {
+    let merged = env
+      .merged(|key| current_value(self, key))
+      .map(|(key, value)| (key.to_os_string(), value))
+      .collect::<Vec<_>>();
+    self.envs(merged)
+  }
+}
+
+/// The value the command passes to the child: either set on the command or inherited from the current process,
+/// which is wrong after `env_clear` (see [`CommandExt::with_shell_env`]).
+fn current_value(cmd: &Command, key: &OsStr) -> Option<std::ffi::OsString> {
+  match cmd.get_envs().find(|(k, _)| *k == key) {
+    Some((_, value)) => value.map(OsStr::to_os_string),
+    None => std::env::var_os(key),
+  }
+}
@@ -0,0 +1,124 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::ffi::{OsStr, OsString};
+
+use crate::{merge::MergeRules, MergePolicy, VarChange};
+
+/// The environment variables resolved from the user's shell.
+///
+/// Variables are kept in the order the shell printed them.
+/// Names and values are [`OsString`]s so values that are not valid UTF-8 are preserved.
+///
+/// The map remembers the [`MergePolicy`] configured for each variable,
+/// which [`ShellEnv::apply`] uses to combine the shell values with the current ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellEnv {
+  vars: Vec<(OsString, OsString)>,
+  merge: MergeRules,
+}
+
+impl ShellEnv {
+  /// Returns the value of the given variable, if the shell defined it.
+  pub fn get(&self, key: impl AsRef<OsStr>) -> Option<&OsStr> {
+    let key = key.as_ref();
+    self
+      .vars
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, v)| v.as_os_str())
+  }
+
+  /// Whether the shell defined the given variable.
+  pub fn contains_key(&self, key: impl AsRef<OsStr>) -> bool {
+    self.get(key).is_some()
+  }
+
+  /// The number of variables.
+  pub fn len(&self) -> usize {
+    self.vars.len()
+  }
+
+  /// Whether no variable was resolved.
+  pub fn is_empty(&self) -> bool {
+    self.vars.is_empty()
+  }
+
+  /// Iterates over the variables in the order the shell printed them.
+  pub fn iter(&self) -> impl Iterator<Item = (&OsStr, &OsStr)> {
+    self
+      .vars
+      .iter()
+      .map(|(k, v)| (k.as_os_str(), v.as_os_str()))
+  }
+
+  /// The policy used to merge the given variable with its current value.
+  pub fn merge_policy(&self, key: impl AsRef<OsStr>) -> MergePolicy {
+    self.merge.policy(key.as_ref())
+  }
+
+  /// Iterates over the variables with their values merged with the `current` ones
+  /// according to their [`MergePolicy`].
+  pub fn merged<'a, F>(&'a self, current: F) -> impl Iterator<Item = (&'a OsStr, OsString)> + 'a
+  where
+    F: Fn(&OsStr) -> Option<OsString> + 'a,
+  {
+    self.iter().map(move |(key, value)| {
+      let current = current(key);
+      (key, self.merge_policy(key).merge(current.as_deref(), value))
+    })
+  }
+
+  /// Compares the variables, merged according to their [`MergePolicy`],
+  /// with their values in the current process without modifying it.
+  pub fn changes(&self) -> Vec<VarChange> {
+    self
+      .merged(|key| std::env::var_os(key))
+      .map(|(key, new)| VarChange {
+        name: key.to_os_string(),
+        old: std::env::var_os(key),
+        new,
+      })
+      .collect()
+  }
+
+  /// Sets all variables on the current process with [`std::env::set_var`],
+  /// merging them with the current values according to their [`MergePolicy`].
+  ///
+  /// Returns how each variable compares to its previous value.
+  pub fn apply(&self) -> Vec<VarChange> {
+    let changes = self.changes();
+    for change in &changes {
+      if !change.is_untouched() {
+        std::env::set_var(&change.name, &change.new);
+      }
+    }
+    changes
+  }
+
+  #[cfg(not(windows))]
+  pub(crate) fn with_merge_rules(mut self, merge: MergeRules) -> Self {
+    self.merge = merge;
+    self
+  }
+
+  /// Inserts a variable, replacing the value in place if it is already defined.
+  #[cfg(not(windows))]
+  pub(crate) fn insert(&mut self, key: OsString, value: OsString) {
+    if let Some(entry) = self.vars.iter_mut().find(|(k, _)| *k == key) {
+      entry.1 = value;
+    } else {
+      self.vars.push((key, value));
+    }
+  }
+}
+
+impl IntoIterator for ShellEnv {
+  type Item = (OsString, OsString);
+  type IntoIter = std::vec::IntoIter<(OsString, OsString)>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.vars.into_iter()
+  }
+}
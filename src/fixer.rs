@@ -0,0 +1,877 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{ffi::OsString, path::PathBuf, sync::Arc, time::Duration};
+
+#[cfg(not(windows))]
+use crate::CapturedOutput;
+use crate::{
+  merge::MergeRules, Error, FixHandle, FixReport, MergePolicy, ShellAdapter,
+  DEFAULT_REDACT_PATTERNS,
+};
+
+/// Variables that only make sense inside the shell session,
+/// skipped by default when every variable is selected (see [`Fixer::excluded_vars`]).
+pub const DEFAULT_EXCLUDED_VARS: &[&str] = &[
+  "PWD",
+  "OLDPWD",
+  "SHLVL",
+  "_",
+  "TERM",
+  "COLUMNS",
+  "LINES",
+  "PS1",
+  "PS2",
+  "PS3",
+  "PS4",
+  "PROMPT",
+  "RPROMPT",
+  "PROMPT_COMMAND",
+  "DISABLE_AUTO_UPDATE",
+];
+
+/// Variables that can inject code into the current process or its children,
+/// blocked by default (see [`Fixer::blocked_vars`]).
+///
+/// A trailing `*` matches every variable starting with the prefix.
+pub const DEFAULT_BLOCKED_VARS: &[&str] = &[
+  "LD_PRELOAD",
+  "LD_LIBRARY_PATH",
+  "LD_AUDIT",
+  "DYLD_*",
+  "GCONV_PATH",
+  "BASH_ENV",
+  "PYTHONSTARTUP",
+  "PERL5OPT",
+  "RUBYOPT",
+  "NODE_OPTIONS",
+  "JAVA_TOOL_OPTIONS",
+  "_JAVA_OPTIONS",
+  "JDK_JAVA_OPTIONS",
+];
+
+/// How old a cache entry can be before it is refreshed in the background, see [`Fixer::cache`].
+pub const DEFAULT_CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The default time the shell has to print its environment.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the resolved variables are applied once the shell has been read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Apply {
+  /// Sets the variables on the current process with [`std::env::set_var`].
+  Process,
+  /// Only resolves the variables, leaving the current process untouched.
+  Skip,
+}
+
+/// What to do when the process runs as root or as a setuid/setgid binary,
+/// where sourcing the user's startup files could escalate their privileges.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrivilegePolicy {
+  /// Fails with [`Error::Privileged`].
+  #[default]
+  Refuse,
+  /// Does not run the shell, resolving no variable.
+  Skip,
+  /// Runs the shell as the real user and group of the process.
+  ///
+  /// Fails with [`Error::Privileged`] when the real user is root, as there is nothing to drop to.
+  DropToRealUser,
+  /// Runs the shell with the privileges of the process.
+  Allow,
+}
+
+/// A user of the passwd database, see [`Fixer::user`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum User {
+  /// A user name.
+  Name(String),
+  /// A user id.
+  Id(u32),
+}
+
+impl From<&str> for User {
+  fn from(name: &str) -> Self {
+    Self::Name(name.into())
+  }
+}
+
+impl From<String> for User {
+  fn from(name: String) -> Self {
+    Self::Name(name)
+  }
+}
+
+impl From<u32> for User {
+  fn from(uid: u32) -> Self {
+    Self::Id(uid)
+  }
+}
+
+impl std::fmt::Display for User {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Name(name) => f.write_str(name),
+      Self::Id(uid) => write!(f, "#{uid}"),
+    }
+  }
+}
+
+/// Configures how the shell is probed and how its environment is applied.
+///
+/// The defaults match [`crate::fix_all_vars`]:
+/// the `SHELL` environment variable (or the login shell from the passwd database
+/// if it is not set or not executable, then `/bin/zsh` on macOS and `/bin/sh` elsewhere)
+/// is run as an interactive login shell (`-ilc` for POSIX shells) from the home directory
+/// and every variable it defines is applied to the current process.
+/// It is not run when the process runs as root or setuid, see [`Fixer::privilege_policy`].
+///
+/// ```no_run
+/// use fix_path_env::{Apply, Fixer};
+///
+/// let report = Fixer::new()
+///   .shell("/bin/bash")
+///   .vars(&["PATH", "JAVA_HOME"])
+///   .apply(Apply::Skip)
+///   .run()
+///   .unwrap();
+/// println!("{:?}", report.env().get("PATH"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fixer {
+  shell: Option<PathBuf>,
+  args: Option<Vec<OsString>>,
+  current_dir: Option<PathBuf>,
+  envs: Vec<(OsString, OsString)>,
+  vars: Vec<String>,
+  excluded_vars: Vec<String>,
+  blocked_vars: Vec<String>,
+  allowed_vars: Vec<String>,
+  apply: Apply,
+  merge: MergeRules,
+  timeout: Duration,
+  redact_patterns: Vec<String>,
+  cache: bool,
+  cache_dir: Option<PathBuf>,
+  cache_max_age: Duration,
+  adapters: Vec<Arc<dyn ShellAdapter>>,
+  strict: bool,
+  shells_file: PathBuf,
+  privileges: PrivilegePolicy,
+  user: Option<User>,
+  sanitize_path: bool,
+  path_options: PathOptions,
+}
+
+/// Which optional checks run when `PATH` is sanitized.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PathOptions {
+  pub drop_missing: bool,
+  pub drop_world_writable: bool,
+}
+
+impl Default for Fixer {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Fixer {
+  /// Creates a new configuration with the default settings.
+  pub fn new() -> Self {
+    Self {
+      shell: None,
+      args: None,
+      current_dir: None,
+      envs: vec![
+        // Disables Oh My Zsh auto-update thing that can block the process.
+        ("DISABLE_AUTO_UPDATE".into(), "true".into()),
+      ],
+      vars: Vec::new(),
+      excluded_vars: DEFAULT_EXCLUDED_VARS
+        .iter()
+        .map(|v| v.to_string())
+        .collect(),
+      blocked_vars: DEFAULT_BLOCKED_VARS.iter().map(|v| v.to_string()).collect(),
+      allowed_vars: Vec::new(),
+      apply: Apply::Process,
+      merge: MergeRules::default(),
+      timeout: DEFAULT_TIMEOUT,
+      redact_patterns: DEFAULT_REDACT_PATTERNS
+        .iter()
+        .map(|p| p.to_string())
+        .collect(),
+      cache: false,
+      cache_dir: None,
+      cache_max_age: DEFAULT_CACHE_MAX_AGE,
+      adapters: Vec::new(),
+      strict: false,
+      shells_file: "/etc/shells".into(),
+      privileges: PrivilegePolicy::default(),
+      user: None,
+      sanitize_path: false,
+      path_options: PathOptions::default(),
+    }
+  }
+
+  /// Sets the shell to run instead of the `SHELL` environment variable.
+  pub fn shell(mut self, shell: impl Into<PathBuf>) -> Self {
+    self.shell.replace(shell.into());
+    self
+  }
+
+  /// Sets the arguments passed to the shell, replacing the default login flags of its [`ShellAdapter`]
+  /// (`-ilc` for POSIX shells, `-l -i -c` for fish, `--login -c` for nu...).
+  ///
+  /// The probe command is appended after these arguments, so the last one is usually `-c`.
+  pub fn args<I, S>(mut self, args: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+  {
+    self.args = Some(args.into_iter().map(Into::into).collect());
+    self
+  }
+
+  /// Sets the working directory of the shell, replacing the default home directory.
+  pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.current_dir.replace(dir.into());
+    self
+  }
+
+  /// Adds an environment variable to the shell process.
+  pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+    self.envs.push((key.into(), value.into()));
+    self
+  }
+
+  /// Selects the variables to resolve.
+  ///
+  /// An empty list selects every variable except the [excluded ones](Self::excluded_vars).
+  pub fn vars(mut self, vars: &[&str]) -> Self {
+    self.vars = vars.iter().map(|v| v.to_string()).collect();
+    self
+  }
+
+  /// Sets the variables skipped when every variable is selected, replacing [`DEFAULT_EXCLUDED_VARS`].
+  pub fn excluded_vars<I, S>(mut self, vars: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: ToString,
+  {
+    self.excluded_vars = vars.into_iter().map(|v| v.to_string()).collect();
+    self
+  }
+
+  /// Adds variables to skip when every variable is selected.
+  pub fn exclude(mut self, vars: &[&str]) -> Self {
+    self
+      .excluded_vars
+      .extend(vars.iter().map(|v| v.to_string()));
+    self
+  }
+
+  /// Sets the variables that are never resolved, even when selected, replacing [`DEFAULT_BLOCKED_VARS`].
+  ///
+  /// A trailing `*` matches every variable starting with the prefix.
+  /// Blocked variables defined by the shell are listed in [`FixReport::blocked`].
+  pub fn blocked_vars<I, S>(mut self, vars: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: ToString,
+  {
+    self.blocked_vars = vars.into_iter().map(|v| v.to_string()).collect();
+    self
+  }
+
+  /// Allows variables that are [blocked](Self::blocked_vars), like `LD_LIBRARY_PATH`.
+  pub fn allow(mut self, vars: &[&str]) -> Self {
+    self.allowed_vars.extend(vars.iter().map(|v| v.to_string()));
+    self
+  }
+
+  /// Sets how the resolved variables are applied.
+  pub fn apply(mut self, apply: Apply) -> Self {
+    self.apply = apply;
+    self
+  }
+
+  /// Sets how the shell value of `var` is combined with its current value,
+  /// overriding the [default policy](Self::default_merge_policy).
+  ///
+  /// ```no_run
+  /// use fix_path_env::{Fixer, MergePolicy};
+  ///
+  /// // keep the directories the app added to PATH first
+  /// Fixer::new()
+  ///   .vars(&["PATH"])
+  ///   .merge_policy("PATH", MergePolicy::Union)
+  ///   .run()
+  ///   .unwrap();
+  /// ```
+  pub fn merge_policy(mut self, var: impl Into<String>, policy: MergePolicy) -> Self {
+    let var = var.into();
+    self.merge.vars.retain(|(v, _)| *v != var);
+    self.merge.vars.push((var, policy));
+    self
+  }
+
+  /// Sets the policy used for the variables without a specific one, defaults to [`MergePolicy::Replace`].
+  pub fn default_merge_policy(mut self, policy: MergePolicy) -> Self {
+    self.merge.default = policy;
+    self
+  }
+
+  /// Sets how long the shell has to print its environment, defaults to [`DEFAULT_TIMEOUT`].
+  ///
+  /// When it expires the whole shell process group is killed and [`Error::Timeout`] is returned.
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = timeout;
+    self
+  }
+
+  /// Sets the variable name patterns whose values are redacted from the output attached to errors,
+  /// replacing [`DEFAULT_REDACT_PATTERNS`].
+  ///
+  /// A variable is sensitive when its name contains one of the patterns, ignoring case.
+  ///
+  /// ```
+  /// use fix_path_env::{Fixer, DEFAULT_REDACT_PATTERNS};
+  ///
+  /// let fixer = Fixer::new().redact_patterns(DEFAULT_REDACT_PATTERNS.iter().chain(&["LICENSE"]));
+  /// ```
+  pub fn redact_patterns<I, S>(mut self, patterns: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: ToString,
+  {
+    self.redact_patterns = patterns.into_iter().map(|p| p.to_string()).collect();
+    self
+  }
+
+  /// Enables or disables the on-disk cache of the resolved environment, disabled by default.
+  ///
+  /// The cache lives in `$XDG_CACHE_HOME/fix-path-env` (`~/Library/Caches/fix-path-env` on macOS)
+  /// and its entries are invalidated when the shell binary or one of its
+  /// [startup files](ShellAdapter::startup_files) (`.profile`, `.zshrc`, `config.fish`...) changes.
+  /// Entries older than the [max age](Self::cache_max_age) are still used,
+  /// but refreshed on a background thread for the next run.
+  ///
+  /// The cached environment may contain secrets, so the files are only readable by the user.
+  /// The cache is never used when running as root or setuid, nor for [another user](Self::user).
+  pub fn cache(mut self, enabled: bool) -> Self {
+    self.cache = enabled;
+    self
+  }
+
+  /// Sets the directory of the [cache](Self::cache).
+  pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.cache_dir.replace(dir.into());
+    self
+  }
+
+  /// Sets how old an entry of the [cache](Self::cache) can be before it is refreshed,
+  /// defaults to [`DEFAULT_CACHE_MAX_AGE`].
+  pub fn cache_max_age(mut self, max_age: Duration) -> Self {
+    self.cache_max_age = max_age;
+    self
+  }
+
+  /// Registers an adapter for shells the crate does not support,
+  /// tried before the built-in ones in registration order.
+  pub fn adapter(mut self, adapter: impl ShellAdapter + 'static) -> Self {
+    self.adapters.push(Arc::new(adapter));
+    self
+  }
+
+  /// Enables or disables the strict mode, disabled by default.
+  ///
+  /// In strict mode the shell must be listed in `/etc/shells` and its binary must not be
+  /// setuid, setgid or writable by everyone, otherwise [`Error::UntrustedShell`] is returned
+  /// instead of running whatever `SHELL` points to.
+  pub fn strict(mut self, strict: bool) -> Self {
+    self.strict = strict;
+    self
+  }
+
+  /// Sets the file read by the [strict mode](Self::strict), `/etc/shells` by default.
+  pub fn shells_file(mut self, path: impl Into<PathBuf>) -> Self {
+    self.shells_file = path.into();
+    self
+  }
+
+  /// Sets what to do when the process runs as root or as a setuid/setgid binary,
+  /// defaults to [`PrivilegePolicy::Refuse`].
+  pub fn privilege_policy(mut self, policy: PrivilegePolicy) -> Self {
+    self.privileges = policy;
+    self
+  }
+
+  /// Enables or disables the sanitization of the shell `PATH`, disabled by default.
+  ///
+  /// `~` is expanded, empty and relative entries are removed and duplicates are removed
+  /// keeping the first occurrence. Each removal is listed in [`FixReport::path_removals`].
+  pub fn sanitize_path(mut self, enabled: bool) -> Self {
+    self.sanitize_path = enabled;
+    self
+  }
+
+  /// Whether the [`PATH` sanitization](Self::sanitize_path) also removes the directories
+  /// that do not exist, disabled by default.
+  pub fn drop_missing_path_dirs(mut self, enabled: bool) -> Self {
+    self.path_options.drop_missing = enabled;
+    self
+  }
+
+  /// Whether the [`PATH` sanitization](Self::sanitize_path) also removes the directories
+  /// writable by everyone, disabled by default.
+  pub fn drop_world_writable_path_dirs(mut self, enabled: bool) -> Self {
+    self.path_options.drop_world_writable = enabled;
+    self
+  }
+
+  /// Resolves the environment of another user instead of the current one.
+  ///
+  /// Their login shell (unless [`Fixer::shell`] is set) and home directory are read from
+  /// the passwd database and the shell runs as that user with their supplementary groups,
+  /// which requires running as root unless it is the current user.
+  /// Like `login`, the shell starts from a clean environment with only `HOME`, `USER`,
+  /// `LOGNAME`, `SHELL`, a default `PATH`, `LANG` and `TERM`, before the [`Fixer::env`] ones.
+  /// The [privilege policy](Self::privilege_policy) does not apply and the cache is not used.
+  ///
+  /// Their variables are returned without touching ours: this sets [`Apply::Skip`],
+  /// call [`Fixer::apply`] afterwards to apply them anyway.
+  pub fn user(mut self, user: impl Into<User>) -> Self {
+    self.user.replace(user.into());
+    self.apply = Apply::Skip;
+    self
+  }
+
+  /// Runs the shell and applies the resolved variables.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Does nothing as the environment variables are already set.
+  pub fn run(&self) -> std::result::Result<FixReport, Error> {
+    #[cfg(windows)]
+    {
+      #[allow(clippy::needless_return)]
+      return Ok(FixReport::default());
+    }
+    #[cfg(not(windows))]
+    {
+      if !self.check_privileges()? {
+        return Ok(FixReport::default());
+      }
+      if let Some(report) = self.cached_report() {
+        return Ok(self.apply_report(report));
+      }
+      let report = self.resolve()?;
+      self.store(&report);
+      Ok(self.apply_report(report))
+    }
+  }
+
+  /// Runs the shell on a background thread, returning a handle to wait for it
+  /// and apply the resolved variables with [`FixHandle::apply`].
+  ///
+  /// The variables are not applied until requested, regardless of [`Fixer::apply`].
+  pub fn spawn(&self) -> FixHandle {
+    let fixer = self.clone().apply(Apply::Skip);
+    FixHandle::new(std::thread::spawn(move || fixer.run()))
+  }
+
+  /// Runs the shell on the tokio runtime and applies the resolved variables.
+  ///
+  /// Dropping the returned future kills the shell process group.
+  /// The work that may block, like looking up the passwd database or reading the cache,
+  /// runs on the blocking thread pool of the runtime.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Does nothing as the environment variables are already set.
+  #[cfg(feature = "tokio")]
+  pub async fn run_async(&self) -> std::result::Result<FixReport, Error> {
+    #[cfg(windows)]
+    {
+      #[allow(clippy::needless_return)]
+      return Ok(FixReport::default());
+    }
+    #[cfg(not(windows))]
+    {
+      use std::ops::ControlFlow;
+
+      let fixer = self.clone();
+      // the report is already complete when the shell does not need to run
+      let prepared = blocking(move || {
+        if !fixer.check_privileges()? {
+          return Ok(ControlFlow::Break(FixReport::default()));
+        }
+        if let Some(report) = fixer.cached_report() {
+          return Ok(ControlFlow::Break(fixer.apply_report(report)));
+        }
+        fixer.probe().map(ControlFlow::Continue)
+      })
+      .await??;
+      let (probe, command) = match prepared {
+        ControlFlow::Continue(probe) => probe,
+        ControlFlow::Break(report) => return Ok(report),
+      };
+      let start = std::time::Instant::now();
+      let out = crate::process::output_async(command, self.timeout, &self.redact_patterns).await?;
+      let report = self.report(probe, out, start.elapsed())?;
+      let fixer = self.clone();
+      blocking(move || {
+        fixer.store(&report);
+        fixer.apply_report(report)
+      })
+      .await
+    }
+  }
+
+  /// Runs the shell and parses its environment, without using the cache or applying anything.
+  #[cfg(not(windows))]
+  fn resolve(&self) -> std::result::Result<FixReport, Error> {
+    let (probe, mut command) = self.probe()?;
+    let start = std::time::Instant::now();
+    let out = crate::process::output(&mut command, self.timeout, &self.redact_patterns)?;
+    self.report(probe, out, start.elapsed())
+  }
+
+  #[cfg(not(windows))]
+  fn disk_cache(&self) -> Option<crate::cache::Cache> {
+    // the startup files of other users are not tracked, and an elevated process must not
+    // write to directories controlled by the user
+    if self.user.is_some() || crate::privileges::Ids::current().is_elevated() {
+      return None;
+    }
+    if !self.cache {
+      return None;
+    }
+    let dir = self.cache_dir.clone().or_else(crate::cache::default_dir)?;
+    Some(crate::cache::Cache::new(
+      dir,
+      (
+        &self.args,
+        &self.current_dir,
+        &self.envs,
+        &self.vars,
+        &self.excluded_vars,
+        self
+          .adapters
+          .iter()
+          .map(|adapter| adapter.name())
+          .collect::<Vec<_>>(),
+      ),
+    ))
+  }
+
+  /// Loads the environment from the cache, refreshing the entry on a background thread when it is too old.
+  #[cfg(not(windows))]
+  fn cached_report(&self) -> Option<FixReport> {
+    let cache = self.disk_cache()?;
+    // an untrusted shell is reported when running it
+    let shell = self.trusted_shell(None).ok()?;
+    let adapter = crate::shell::adapter(&shell, &self.adapters);
+    let args = self.args.clone().unwrap_or_else(|| adapter.args(&shell));
+    let start = std::time::Instant::now();
+    let (records, age) = cache.load(&shell, adapter)?;
+
+    if age > self.cache_max_age {
+      let fixer = self.clone();
+      std::thread::spawn(move || {
+        if let Ok(report) = fixer.resolve() {
+          fixer.store(&report);
+        }
+      });
+    }
+
+    let (env, blocked) = self.select(records);
+    Some(FixReport {
+      env,
+      changes: Vec::new(),
+      blocked,
+      path_removals: Vec::new(),
+      shell,
+      args,
+      status: None,
+      duration: start.elapsed(),
+      cached: true,
+    })
+  }
+
+  #[cfg(not(windows))]
+  fn store(&self, report: &FixReport) {
+    if let Some(cache) = self.disk_cache() {
+      // the cache is an optimization, failing to write it must not fail the fix
+      // blocked variables are stored too, so they are still reported when the entry is loaded
+      let adapter = crate::shell::adapter(&report.shell, &self.adapters);
+      let _ = cache.store(
+        &report.shell,
+        adapter,
+        report.env.iter().chain(report.blocked()),
+      );
+    }
+  }
+
+  #[cfg(not(windows))]
+  fn probe(&self) -> std::result::Result<(Probe, std::process::Command), Error> {
+    let user = self
+      .user
+      .as_ref()
+      .map(|user| crate::passwd::Passwd::find(user).ok_or_else(|| Error::UnknownUser(user.clone())))
+      .transpose()?;
+    let shell = self.trusted_shell(user.as_ref())?;
+    let adapter = crate::shell::adapter(&shell, &self.adapters);
+
+    let delimiter = crate::Delimiter::random();
+    let mut args = self.args.clone().unwrap_or_else(|| adapter.args(&shell));
+    args.push(adapter.script(&delimiter).into());
+
+    let mut command = adapter.command(&shell);
+
+    command.args(&args);
+    if let Some(user) = &user {
+      // like `login`, nothing of our environment reaches the other user
+      command
+        .env_clear()
+        .env("HOME", &user.home)
+        .env("USER", &user.name)
+        .env("LOGNAME", &user.name)
+        .env(
+          "SHELL",
+          if user.shell.as_os_str().is_empty() {
+            &shell
+          } else {
+            &user.shell
+          },
+        )
+        .env("PATH", "/usr/local/bin:/usr/bin:/bin");
+      for name in ["LANG", "TERM"] {
+        if let Some(value) = std::env::var_os(name) {
+          command.env(name, value);
+        }
+      }
+    }
+    command
+      .envs(adapter.envs(&shell))
+      .envs(self.envs.iter().map(|(k, v)| (k, v)));
+
+    let home = match &user {
+      Some(user) => Some(user.home.clone()),
+      None => home::home_dir(),
+    };
+    if let Some(dir) = self.current_dir.clone().or(home) {
+      command.current_dir(dir);
+    }
+
+    let ids = crate::privileges::Ids::current();
+    if let Some(user) = &user {
+      if user.uid != ids.euid || user.gid != ids.egid {
+        let groups = crate::privileges::groups(&user.name, user.gid);
+        crate::privileges::run_as(&mut command, user.uid, user.gid, groups);
+      }
+    } else if self.privileges == PrivilegePolicy::DropToRealUser && ids.is_elevated() {
+      crate::privileges::run_as(&mut command, ids.uid, ids.gid, vec![ids.gid]);
+    }
+
+    Ok((
+      Probe {
+        shell,
+        args,
+        delimiter,
+      },
+      command,
+    ))
+  }
+
+  /// Returns whether the shell may run according to the [`PrivilegePolicy`].
+  #[cfg(not(windows))]
+  fn check_privileges(&self) -> std::result::Result<bool, Error> {
+    let ids = crate::privileges::Ids::current();
+    match self.privileges {
+      // the shell runs as the target user
+      _ if !ids.is_elevated() || self.user.is_some() => Ok(true),
+      PrivilegePolicy::Skip => Ok(false),
+      PrivilegePolicy::Allow => Ok(true),
+      PrivilegePolicy::DropToRealUser if ids.uid != 0 => Ok(true),
+      PrivilegePolicy::Refuse | PrivilegePolicy::DropToRealUser => Err(Error::Privileged {
+        uid: ids.uid,
+        euid: ids.euid,
+      }),
+    }
+  }
+
+  /// The shell to run, checked against the shells file in strict mode.
+  #[cfg(not(windows))]
+  fn trusted_shell(
+    &self,
+    user: Option<&crate::passwd::Passwd>,
+  ) -> std::result::Result<PathBuf, Error> {
+    let shell = match (&self.shell, user) {
+      (Some(shell), _) => shell.clone(),
+      // an empty login shell means `/bin/sh`
+      (None, Some(user)) if user.shell.as_os_str().is_empty() => "/bin/sh".into(),
+      (None, Some(user)) => user.shell.clone(),
+      (None, None) => default_shell(),
+    };
+    if self.strict {
+      crate::trust::check(&shell, &self.shells_file)?;
+    }
+    Ok(shell)
+  }
+
+  /// Parses the probe output into a report, without any change yet.
+  #[cfg(not(windows))]
+  fn report(
+    &self,
+    probe: Probe,
+    out: crate::process::Output,
+    duration: Duration,
+  ) -> std::result::Result<FixReport, Error> {
+    if out.status.success() {
+      let env = probe
+        .delimiter
+        .extract(&out.stdout)
+        .map_err(|marker| Error::InvalidOutput {
+          marker,
+          stdout: CapturedOutput::new(out.stdout.clone(), &self.redact_patterns),
+        })?;
+      let vars = crate::shell::adapter(&probe.shell, &self.adapters)
+        .parse(env)
+        .map_err(Error::InvalidEnv)?;
+      let (env, blocked) = self.select(vars);
+      Ok(FixReport {
+        env,
+        changes: Vec::new(),
+        blocked,
+        path_removals: Vec::new(),
+        shell: probe.shell,
+        args: probe.args,
+        status: Some(out.status),
+        duration,
+        cached: false,
+      })
+    } else {
+      Err(Error::EchoFailed {
+        code: out.status.code(),
+        stderr: CapturedOutput::new(out.stderr, &self.redact_patterns),
+      })
+    }
+  }
+
+  #[cfg(not(windows))]
+  fn apply_report(&self, mut report: FixReport) -> FixReport {
+    // sanitized here rather than when parsing, so the cache keeps the shell value
+    if let Some(path) = report.env.get("PATH").filter(|_| self.sanitize_path) {
+      let home = match (report.env.get("HOME"), &self.user) {
+        (Some(home), _) => Some(PathBuf::from(home)),
+        (None, Some(user)) => crate::passwd::Passwd::find(user).map(|p| p.home),
+        (None, None) => home::home_dir(),
+      };
+      let (path, removals) = crate::sanitize::sanitize(path, home.as_deref(), self.path_options);
+      report.env.insert("PATH".into(), path);
+      report.path_removals = removals;
+    }
+    report.changes = if self.apply == Apply::Process {
+      report.env.apply()
+    } else {
+      report.env.changes()
+    };
+    report
+  }
+
+  /// Keeps the selected variables, separating the blocked ones.
+  #[cfg(not(windows))]
+  fn select(
+    &self,
+    vars: Vec<(OsString, OsString)>,
+  ) -> (crate::ShellEnv, Vec<(OsString, OsString)>) {
+    let mut env = crate::ShellEnv::default().with_merge_rules(self.merge.clone());
+    let mut blocked = Vec::new();
+    for (var, value) in vars {
+      if !self.is_selected(&var) {
+        continue;
+      }
+      if self.is_blocked(&var) {
+        blocked.push((var, value));
+      } else {
+        env.insert(var, value);
+      }
+    }
+    (env, blocked)
+  }
+
+  #[cfg(not(windows))]
+  fn is_blocked(&self, var: &std::ffi::OsStr) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let var = var.as_bytes();
+    let matches = |pattern: &String| match pattern.strip_suffix('*') {
+      Some(prefix) => var.starts_with(prefix.as_bytes()),
+      None => var == pattern.as_bytes(),
+    };
+    self.blocked_vars.iter().any(matches) && !self.allowed_vars.iter().any(|v| var == v.as_bytes())
+  }
+
+  #[cfg(not(windows))]
+  fn is_selected(&self, var: &std::ffi::OsStr) -> bool {
+    if self.vars.is_empty() {
+      !self.excluded_vars.iter().any(|v| var == v.as_str())
+    } else {
+      self.vars.iter().any(|v| var == v.as_str())
+    }
+  }
+}
+
+/// Runs blocking work on the tokio blocking thread pool, resuming its panics.
+#[cfg(all(feature = "tokio", not(windows)))]
+async fn blocking<T: Send + 'static>(
+  f: impl FnOnce() -> T + Send + 'static,
+) -> std::result::Result<T, Error> {
+  match tokio::task::spawn_blocking(f).await {
+    Ok(value) => Ok(value),
+    Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+    // the runtime is shutting down
+    Err(e) => Err(Error::Shell(std::io::Error::other(e))),
+  }
+}
+
+/// How the shell command built from a [`Fixer`] was set up.
+#[cfg(not(windows))]
+struct Probe {
+  shell: PathBuf,
+  args: Vec<OsString>,
+  delimiter: crate::Delimiter,
+}
+
+/// The shell of the user: `SHELL` if it points to an executable,
+/// then the login shell from the passwd database, then `/bin/zsh` on macOS or `/bin/sh` elsewhere.
+///
+/// `SHELL` is often missing or wrong when the app is started by systemd, cron or a desktop launcher.
+#[cfg(not(windows))]
+fn default_shell() -> PathBuf {
+  std::env::var_os("SHELL")
+    .map(PathBuf::from)
+    .into_iter()
+    .chain(std::iter::once_with(|| crate::passwd::Passwd::current().map(|p| p.shell)).flatten())
+    .find(|shell| is_executable(shell))
+    .unwrap_or_else(|| {
+      if cfg!(target_os = "macos") {
+        "/bin/zsh"
+      } else {
+        "/bin/sh"
+      }
+      .into()
+    })
+}
+
+#[cfg(not(windows))]
+fn is_executable(path: &std::path::Path) -> bool {
+  use std::os::unix::fs::PermissionsExt;
+
+  path.is_absolute()
+    && std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
@@ -2,76 +2,187 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+mod adapter;
+mod background;
+#[cfg(not(windows))]
+mod cache;
+mod command;
+mod env;
+mod fixer;
+mod merge;
+mod output;
+#[cfg(not(windows))]
+mod parse;
+#[cfg(not(windows))]
+mod passwd;
+#[cfg(not(windows))]
+mod privileges;
+#[cfg(not(windows))]
+mod process;
+mod report;
+#[cfg(not(windows))]
+mod sanitize;
+#[cfg(not(windows))]
+mod shell;
+#[cfg(not(windows))]
+mod trust;
+
+pub use adapter::{posix_startup_files, Delimiter, ShellAdapter};
+pub use background::FixHandle;
+pub use command::CommandExt;
+pub use env::ShellEnv;
+pub use fixer::{
+  Apply, Fixer, PrivilegePolicy, User, DEFAULT_BLOCKED_VARS, DEFAULT_CACHE_MAX_AGE,
+  DEFAULT_EXCLUDED_VARS, DEFAULT_TIMEOUT,
+};
+pub use merge::MergePolicy;
+pub use output::{CapturedOutput, DEFAULT_REDACT_PATTERNS};
+pub use report::{FixReport, PathRemoval, PathRemovalReason, VarChange};
+
 /// The error that might happen on a [`fix`] call.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
   #[error(transparent)]
   Shell(#[from] std::io::Error),
-  #[error("invalid output from shell echo: {0}")]
-  InvalidOutput(String),
-  #[error("failed to run shell echo: {0}")]
-  EchoFailed(String),
+  #[error("invalid output from shell echo: {marker}")]
+  InvalidOutput {
+    /// What is wrong with the markers surrounding the environment dump.
+    #[source]
+    marker: MarkerError,
+    /// What the shell wrote to stdout.
+    stdout: CapturedOutput,
+  },
+  #[error("failed to parse the shell environment: {0}")]
+  InvalidEnv(String),
+  #[error("failed to run shell echo: {stderr}")]
+  EchoFailed {
+    /// The exit code of the shell, if it was not killed by a signal.
+    code: Option<i32>,
+    /// What the shell wrote to stderr.
+    stderr: CapturedOutput,
+  },
+  #[error("shell did not finish after {elapsed:?}, the process group was killed")]
+  Timeout {
+    /// How long the shell ran before being killed.
+    elapsed: std::time::Duration,
+    /// What the shell wrote to stderr before being killed.
+    partial_stderr: CapturedOutput,
+  },
+  #[error("refusing to run untrusted shell {}: {reason}", shell.display())]
+  UntrustedShell {
+    /// The shell that was rejected.
+    shell: std::path::PathBuf,
+    /// Why it was rejected.
+    reason: UntrustedReason,
+  },
+  #[error("refusing to run the shell with elevated privileges (uid {uid}, effective uid {euid})")]
+  Privileged {
+    /// The real user id of the process.
+    uid: u32,
+    /// The effective user id of the process.
+    euid: u32,
+  },
+  #[error("user {0} was not found in the passwd database")]
+  UnknownUser(User),
 }
 
-/// Reads the shell configuration to properly set all given environment variables.
+/// The problem with the markers the shell prints around its environment, see [`Error::InvalidOutput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum MarkerError {
+  #[error("the opening marker is missing")]
+  MissingOpening,
+  #[error("the closing marker is missing")]
+  MissingClosing,
+  #[error("the opening marker was printed more than once")]
+  DuplicateOpening,
+  #[error("the closing marker was printed more than once")]
+  DuplicateClosing,
+}
+
+/// Why a shell was rejected by [`Fixer::strict`], see [`Error::UntrustedShell`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum UntrustedReason {
+  #[error("it is not listed in the shells file")]
+  NotListed,
+  #[error("it has the setuid or setgid bit")]
+  Setuid,
+  #[error("it is writable by everyone")]
+  WorldWritable,
+}
+
+/// Reads the shell configuration and returns the given environment variables without
+/// modifying the current process.
+///
+/// An empty `vars` slice returns every variable defined by the shell.
 ///
 /// ## Platform-specific
 ///
-/// - **Windows**: Does nothing as the environment variables are already set.
-pub fn fix_vars(vars: &[&str]) -> std::result::Result<(), Error> {
-  #[cfg(windows)]
-  {
-    let _ = vars;
-    #[allow(clippy::needless_return)]
-    return Ok(());
-  }
-  #[cfg(not(windows))]
-  {
-    let default_shell = if cfg!(target_os = "macos") {
-      "/bin/zsh"
-    } else {
-      "/bin/sh"
-    };
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| default_shell.into());
-
-    let mut cmd = std::process::Command::new(shell);
+/// - **Windows**: Returns an empty map as the environment variables are already set.
+pub fn shell_env(vars: &[&str]) -> std::result::Result<ShellEnv, Error> {
+  Fixer::new()
+    .vars(vars)
+    .apply(Apply::Skip)
+    .run()
+    .map(FixReport::into_env)
+}
 
-    cmd
-      .arg("-ilc")
-      .arg("echo -n \"_SHELL_ENV_DELIMITER_\"; env; echo -n \"_SHELL_ENV_DELIMITER_\"; exit")
-      // Disables Oh My Zsh auto-update thing that can block the process.
-      .env("DISABLE_AUTO_UPDATE", "true");
+/// Reads the shell configuration of another user and returns the given environment variables
+/// without modifying the current process, see [`Fixer::user`].
+///
+/// ```no_run
+/// let env = fix_path_env::user_env("alice", &["PATH"]).unwrap();
+/// ```
+///
+/// ## Platform-specific
+///
+/// - **Windows**: Returns an empty map as the environment variables are already set.
+pub fn user_env(user: impl Into<User>, vars: &[&str]) -> std::result::Result<ShellEnv, Error> {
+  Fixer::new()
+    .user(user)
+    .vars(vars)
+    .apply(Apply::Skip)
+    .run()
+    .map(FixReport::into_env)
+}
 
-    if let Some(home) = home::home_dir() {
-      cmd.current_dir(home);
-    }
+/// Like [`shell_env`] but runs the shell on the tokio runtime instead of blocking the thread.
+///
+/// Dropping the returned future kills the shell.
+///
+/// ## Platform-specific
+///
+/// - **Windows**: Returns an empty map as the environment variables are already set.
+#[cfg(feature = "tokio")]
+pub async fn shell_env_async(vars: &[&str]) -> std::result::Result<ShellEnv, Error> {
+  Fixer::new()
+    .vars(vars)
+    .apply(Apply::Skip)
+    .run_async()
+    .await
+    .map(FixReport::into_env)
+}
 
-    let out = cmd.output().map_err(Error::Shell)?;
+/// Reads the shell configuration to properly set all given environment variables.
+///
+/// Returns a [`FixReport`] describing what changed.
+///
+/// ## Platform-specific
+///
+/// - **Windows**: Does nothing as the environment variables are already set.
+pub fn fix_vars(vars: &[&str]) -> std::result::Result<FixReport, Error> {
+  Fixer::new().vars(vars).run()
+}
 
-    if out.status.success() {
-      let stdout = String::from_utf8_lossy(&out.stdout).into_owned();
-      let env = stdout
-        .split("_SHELL_ENV_DELIMITER_")
-        .nth(1)
-        .ok_or_else(|| Error::InvalidOutput(stdout.clone()))?;
-      for line in String::from_utf8_lossy(&strip_ansi_escapes::strip(env))
-        .split('\n')
-        .filter(|l| !l.is_empty())
-      {
-        let mut s = line.splitn(2, '=');
-        if let (Some(var), Some(value)) = (s.next(), s.next()) {
-          if vars.is_empty() || vars.contains(&var) {
-            std::env::set_var(var, value);
-          }
-        }
-      }
-      Ok(())
-    } else {
-      Err(Error::EchoFailed(
-        String::from_utf8_lossy(&out.stderr).into_owned(),
-      ))
-    }
-  }
+/// Like [`fix_vars`] but runs the shell on the tokio runtime instead of blocking the thread.
+///
+/// Dropping the returned future kills the shell.
+///
+/// ## Platform-specific
+///
+/// - **Windows**: Does nothing as the environment variables are already set.
+#[cfg(feature = "tokio")]
+pub async fn fix_vars_async(vars: &[&str]) -> std::result::Result<FixReport, Error> {
+  Fixer::new().vars(vars).run_async().await
 }
 
 /// Reads the shell configuration to properly set the PATH environment variable.
@@ -79,15 +190,33 @@ pub fn fix_vars(vars: &[&str]) -> std::result::Result<(), Error> {
 /// ## Platform-specific
 ///
 /// - **Windows**: Does nothing as the environment variables are already set.
-pub fn fix() -> std::result::Result<(), Error> {
-  fix_vars(&["PATH"])
+pub fn fix() -> std::result::Result<FixReport, Error> {
+  Fixer::new().vars(&["PATH"]).run()
+}
+
+/// Starts reading the shell configuration on a background thread, returning a handle
+/// to set the PATH environment variable once it is needed.
+///
+/// ```no_run
+/// let handle = fix_path_env::fix_in_background();
+/// // build the window...
+/// let _ = handle.apply();
+/// ```
+///
+/// ## Platform-specific
+///
+/// - **Windows**: Does nothing as the environment variables are already set.
+pub fn fix_in_background() -> FixHandle {
+  Fixer::new().vars(&["PATH"]).spawn()
 }
 
-/// Reads the shell configuration to properly set all environment variables.
+/// Reads the shell configuration to properly set all environment variables,
+/// except the ones in [`DEFAULT_EXCLUDED_VARS`] that only make sense inside the shell session
+/// and the ones in [`DEFAULT_BLOCKED_VARS`] that could inject code.
 ///
 /// ## Platform-specific
 ///
 /// - **Windows**: Does nothing as the environment variables are already set.
-pub fn fix_all_vars() -> std::result::Result<(), Error> {
-  fix_vars(&[])
+pub fn fix_all_vars() -> std::result::Result<FixReport, Error> {
+  Fixer::new().run()
 }
@@ -0,0 +1,77 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::ffi::{OsStr, OsString};
+
+/// How the value read from the shell is combined with the value the variable already has.
+///
+/// List policies split values with [`std::env::split_paths`],
+/// so they use `:` as separator (`;` on Windows) like `PATH`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+  /// Uses the shell value.
+  #[default]
+  Replace,
+  /// Puts the shell value before the existing one.
+  Prepend,
+  /// Puts the shell value after the existing one.
+  Append,
+  /// Keeps the existing value, only using the shell value if the variable is not set.
+  KeepExisting,
+  /// Keeps the existing entries and adds the shell entries that are missing,
+  /// removing duplicates while keeping the first occurrence.
+  Union,
+}
+
+impl MergePolicy {
+  /// Combines the `current` value of a variable with the value read from the shell.
+  pub fn merge(self, current: Option<&OsStr>, shell: &OsStr) -> OsString {
+    let current = current.filter(|c| !c.is_empty());
+    match (self, current) {
+      (Self::Union, current) => {
+        let mut entries = Vec::new();
+        for entry in current
+          .into_iter()
+          .chain([shell])
+          .flat_map(std::env::split_paths)
+        {
+          if !entries.contains(&entry) {
+            entries.push(entry);
+          }
+        }
+        std::env::join_paths(entries).unwrap_or_else(|_| shell.to_os_string())
+      }
+      (Self::Replace, _) | (_, None) => shell.to_os_string(),
+      (Self::KeepExisting, Some(current)) => current.to_os_string(),
+      (Self::Prepend, Some(current)) => join([shell, current]),
+      (Self::Append, Some(current)) => join([current, shell]),
+    }
+  }
+}
+
+fn join(values: [&OsStr; 2]) -> OsString {
+  let separator = if cfg!(windows) { ";" } else { ":" };
+  let mut joined = values[0].to_os_string();
+  joined.push(separator);
+  joined.push(values[1]);
+  joined
+}
+
+/// The merge policies configured on a [`crate::Fixer`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct MergeRules {
+  pub default: MergePolicy,
+  pub vars: Vec<(String, MergePolicy)>,
+}
+
+impl MergeRules {
+  pub fn policy(&self, key: &OsStr) -> MergePolicy {
+    self
+      .vars
+      .iter()
+      .find(|(k, _)| key == k.as_str())
+      .map(|(_, p)| *p)
+      .unwrap_or(self.default)
+  }
+}
@@ -0,0 +1,221 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::fmt;
+
+/// The variable name patterns whose values are redacted from [`CapturedOutput`] by default.
+///
+/// A variable is sensitive when its name contains one of them, ignoring case.
+pub const DEFAULT_REDACT_PATTERNS: &[&str] = &[
+  "SECRET",
+  "TOKEN",
+  "PASSWORD",
+  "PASSWD",
+  "PASSPHRASE",
+  "CREDENTIAL",
+  "PRIVATE",
+  "API_KEY",
+  "ACCESS_KEY",
+  "AUTH",
+  "COOKIE",
+  "SESSION",
+];
+
+/// How many bytes of the redacted output are kept at its start and at its end.
+#[cfg(not(windows))]
+const KEEP: usize = 1024;
+
+#[cfg(not(windows))]
+const REDACTED: &str = "[REDACTED]";
+
+/// Output captured from the shell and attached to an [`crate::Error`].
+///
+/// Its [`Display`](fmt::Display) and [`Debug`] implementations only show a truncated copy
+/// where the values of sensitive variables are replaced by `[REDACTED]`,
+/// so errors can be logged without leaking secrets.
+/// The raw output is available through [`CapturedOutput::unredacted`].
+#[derive(Clone)]
+pub struct CapturedOutput {
+  raw: Vec<u8>,
+  redacted: String,
+}
+
+impl CapturedOutput {
+  #[cfg(not(windows))]
+  pub(crate) fn new(raw: Vec<u8>, patterns: &[String]) -> Self {
+    let redacted = truncate(&redact(&String::from_utf8_lossy(&raw), patterns));
+    Self { raw, redacted }
+  }
+
+  /// The truncated output with the values of sensitive variables redacted.
+  pub fn redacted(&self) -> &str {
+    &self.redacted
+  }
+
+  /// The full output as the shell printed it, including the values of sensitive variables.
+  ///
+  /// Do not log or display this without the user's consent.
+  pub fn unredacted(&self) -> &[u8] {
+    &self.raw
+  }
+}
+
+impl fmt::Display for CapturedOutput {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.redacted)
+  }
+}
+
+impl fmt::Debug for CapturedOutput {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Debug::fmt(&self.redacted, f)
+  }
+}
+
+/// Whether the variable name matches one of the sensitive `patterns`.
+#[cfg(not(windows))]
+pub(crate) fn is_sensitive(name: &str, patterns: &[String]) -> bool {
+  let name = name.to_ascii_uppercase();
+  patterns
+    .iter()
+    .any(|p| name.contains(&p.to_ascii_uppercase()))
+}
+
+/// Replaces everything after `NAME=` in each record where `NAME` is sensitive.
+///
+/// NUL-separated dumps are split on NUL. Otherwise each line is a record, except lines without
+/// a `NAME=` prefix following a redacted record: they are the rest of a multiline value
+/// and are dropped.
+#[cfg(not(windows))]
+fn redact(text: &str, patterns: &[String]) -> String {
+  let separator = if text.contains('\0') { '\0' } else { '\n' };
+  let mut out = String::with_capacity(text.len());
+  let mut redacting = false;
+  for record in text.split_inclusive(separator) {
+    let content = record.trim_end_matches(separator);
+    if separator == '\n' && redacting && !starts_with_name(content) {
+      continue;
+    }
+    match redact_record(content, patterns) {
+      Some(redacted) => {
+        out.push_str(&redacted);
+        redacting = true;
+      }
+      None => {
+        out.push_str(content);
+        redacting = false;
+      }
+    }
+    out.push_str(&record[content.len()..]);
+  }
+  out
+}
+
+/// The record with the value of its first sensitive variable redacted, if any.
+#[cfg(not(windows))]
+fn redact_record(record: &str, patterns: &[String]) -> Option<String> {
+  let mut search = 0;
+  while let Some(eq) = record[search..].find('=').map(|i| i + search) {
+    let name_start = record[..eq]
+      .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+      .map(|i| i + 1)
+      .unwrap_or(0);
+    if is_sensitive(&record[name_start..eq], patterns) {
+      return Some(format!("{}={REDACTED}", &record[..eq]));
+    }
+    search = eq + 1;
+  }
+  None
+}
+
+/// Whether the line starts with `NAME=`, like the records of `env`.
+#[cfg(not(windows))]
+fn starts_with_name(line: &str) -> bool {
+  line.split_once('=').is_some_and(|(name, _)| {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+  })
+}
+
+#[cfg(not(windows))]
+fn truncate(text: &str) -> String {
+  if text.len() <= KEEP * 2 {
+    return text.to_string();
+  }
+  let mut head = KEEP;
+  while !text.is_char_boundary(head) {
+    head -= 1;
+  }
+  let mut tail = text.len() - KEEP;
+  while !text.is_char_boundary(tail) {
+    tail += 1;
+  }
+  format!(
+    "{}\n[... {} bytes truncated ...]\n{}",
+    &text[..head],
+    tail - head,
+    &text[tail..]
+  )
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+  use super::*;
+
+  fn patterns() -> Vec<String> {
+    DEFAULT_REDACT_PATTERNS
+      .iter()
+      .map(|p| p.to_string())
+      .collect()
+  }
+
+  #[test]
+  fn redact_nul_records() {
+    let text = "PATH=/bin\0GITHUB_TOKEN=first\nsecond\0HOME=/home/me\0";
+    assert_eq!(
+      redact(text, &patterns()),
+      "PATH=/bin\0GITHUB_TOKEN=[REDACTED]\0HOME=/home/me\0"
+    );
+  }
+
+  #[test]
+  fn redact_lines() {
+    let text =
+      "PATH=/bin\nexport API_KEY=first\nsecond line\n-----END-----\nHOME=/home/me\nplain text\n";
+    assert_eq!(
+      redact(text, &patterns()),
+      "PATH=/bin\nexport API_KEY=[REDACTED]\nHOME=/home/me\nplain text\n"
+    );
+  }
+
+  #[test]
+  fn truncate_on_char_boundary() {
+    // the multi-byte characters straddle both cut points
+    let text = format!(
+      "{}{}{}",
+      "a".repeat(KEEP - 1),
+      "é".repeat(KEEP),
+      "b".repeat(KEEP - 1)
+    );
+    let truncated = truncate(&text);
+    assert!(truncated.starts_with(&"a".repeat(KEEP - 1)));
+    assert!(truncated.ends_with(&"b".repeat(KEEP - 1)));
+    assert!(truncated.contains(" bytes truncated ..."));
+    assert!(truncated.len() < text.len());
+  }
+
+  #[test]
+  fn error_hides_secrets() {
+    let stderr = b"DB_PASSWORD=hunter2\nstill hunter2\n".to_vec();
+    let error = crate::Error::EchoFailed {
+      code: Some(1),
+      stderr: CapturedOutput::new(stderr.clone(), &patterns()),
+    };
+    assert!(!error.to_string().contains("hunter2"));
+    assert!(!format!("{error:?}").contains("hunter2"));
+    if let crate::Error::EchoFailed { stderr: output, .. } = &error {
+      assert!(output.redacted().contains("DB_PASSWORD=[REDACTED]"));
+      assert_eq!(output.unredacted(), stderr);
+    }
+  }
+}
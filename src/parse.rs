@@ -0,0 +1,39 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{borrow::Cow, ffi::OsString, os::unix::ffi::OsStringExt};
+
+const ESC: u8 = 0x1b;
+
+/// Parses the output of `env -0` (or `env` as a fallback) into `(name, value)` pairs.
+///
+/// The format is detected from the dump itself: variables can never contain a NUL byte,
+/// so its presence means every record is NUL-terminated and values may span several lines.
+/// The dump is parsed as raw bytes so values that are not valid UTF-8 are preserved.
+pub(crate) fn parse_env(dump: &[u8]) -> Vec<(OsString, OsString)> {
+  let separator = if dump.contains(&0) { 0 } else { b'\n' };
+  let mut vars = Vec::new();
+  for record in dump.split(|b| *b == separator).filter(|r| !r.is_empty()) {
+    // shell integrations may print terminal escape sequences (colors, titles...).
+    // Stripping them decodes the record as UTF-8, so records without any are kept as is.
+    let record = if record.contains(&ESC) {
+      Cow::Owned(strip_ansi_escapes::strip(record))
+    } else {
+      Cow::Borrowed(record)
+    };
+    // leftovers from the shell (like a trailing newline) before the first variable
+    let start = record
+      .iter()
+      .position(|b| *b != b'\n')
+      .unwrap_or(record.len());
+    let record = &record[start..];
+    if let Some(eq) = record.iter().position(|b| *b == b'=') {
+      vars.push((
+        OsString::from_vec(record[..eq].to_vec()),
+        OsString::from_vec(record[eq + 1..].to_vec()),
+      ));
+    }
+  }
+  vars
+}
@@ -0,0 +1,139 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::{CStr, CString, OsStr, OsString},
+  os::unix::ffi::OsStrExt,
+  path::{Path, PathBuf},
+  process::{Command, Stdio},
+};
+
+use crate::User;
+
+/// An entry of the passwd database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Passwd {
+  pub name: OsString,
+  pub uid: u32,
+  pub gid: u32,
+  pub home: PathBuf,
+  pub shell: PathBuf,
+}
+
+impl Passwd {
+  /// Looks up the entry of the given user id with `getpwuid_r`, then `getent passwd`
+  /// (which still sees the NSS databases when we are statically linked) and finally `/etc/passwd`.
+  pub fn from_uid(uid: u32) -> Option<Self> {
+    getpwuid(uid)
+      .or_else(|| getent(&uid.to_string()))
+      .or_else(|| find_in_file(Path::new("/etc/passwd"), |entry| entry.uid == uid))
+  }
+
+  /// Looks up the entry of the given user name, see [`Passwd::from_uid`].
+  pub fn from_name(name: &str) -> Option<Self> {
+    getpwnam(name)
+      .or_else(|| getent(name))
+      .or_else(|| find_in_file(Path::new("/etc/passwd"), |entry| entry.name == name))
+  }
+
+  /// Looks up the entry of a [`User`].
+  pub fn find(user: &User) -> Option<Self> {
+    match user {
+      User::Name(name) => Self::from_name(name),
+      User::Id(uid) => Self::from_uid(*uid),
+    }
+  }
+
+  /// The entry of the real user of the process.
+  pub fn current() -> Option<Self> {
+    // SAFETY: `getuid` has no preconditions and cannot fail.
+    Self::from_uid(unsafe { libc::getuid() })
+  }
+
+  /// Parses a `name:password:uid:gid:gecos:home:shell` line.
+  fn parse(line: &[u8]) -> Option<Self> {
+    let fields = line.split(|b| *b == b':').collect::<Vec<_>>();
+    let [name, _, uid, gid, _, home, shell] = fields.as_slice() else {
+      return None;
+    };
+    let number = |field: &[u8]| std::str::from_utf8(field).ok()?.parse().ok();
+    Some(Self {
+      name: OsStr::from_bytes(name).to_os_string(),
+      uid: number(uid)?,
+      gid: number(gid)?,
+      home: OsStr::from_bytes(home).into(),
+      shell: OsStr::from_bytes(shell).into(),
+    })
+  }
+}
+
+fn getpwuid(uid: u32) -> Option<Passwd> {
+  // SAFETY: the arguments come from `lookup`, which passes valid pointers.
+  lookup(|entry, buf, len, result| unsafe { libc::getpwuid_r(uid, entry, buf, len, result) })
+}
+
+fn getpwnam(name: &str) -> Option<Passwd> {
+  let name = CString::new(name).ok()?;
+  // SAFETY: the arguments come from `lookup`, which passes valid pointers.
+  lookup(|entry, buf, len, result| unsafe {
+    libc::getpwnam_r(name.as_ptr(), entry, buf, len, result)
+  })
+}
+
+/// Calls a `getpw*_r` function, growing its buffer as needed.
+fn lookup(
+  getpw: impl Fn(
+    *mut libc::passwd,
+    *mut libc::c_char,
+    libc::size_t,
+    *mut *mut libc::passwd,
+  ) -> libc::c_int,
+) -> Option<Passwd> {
+  // SAFETY: `passwd` is a plain C struct, only read after the lookup filled it.
+  let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+  let mut result = std::ptr::null_mut();
+  let mut buf = vec![0u8; 1024];
+  loop {
+    let code = getpw(&mut entry, buf.as_mut_ptr().cast(), buf.len(), &mut result);
+    if code == libc::ERANGE && buf.len() < 1 << 20 {
+      buf.resize(buf.len() * 2, 0);
+      continue;
+    }
+    break;
+  }
+  if result.is_null() {
+    return None;
+  }
+  // SAFETY: on success the fields point to NUL-terminated strings in `buf`.
+  let field = |ptr: *const libc::c_char| {
+    (!ptr.is_null()).then(|| OsStr::from_bytes(unsafe { CStr::from_ptr(ptr) }.to_bytes()))
+  };
+  Some(Passwd {
+    name: field(entry.pw_name)?.to_os_string(),
+    uid: entry.pw_uid,
+    gid: entry.pw_gid,
+    home: field(entry.pw_dir)?.into(),
+    shell: field(entry.pw_shell).unwrap_or_default().into(),
+  })
+}
+
+fn getent(key: &str) -> Option<Passwd> {
+  let output = Command::new("getent")
+    .args(["passwd", key])
+    .stdin(Stdio::null())
+    .stderr(Stdio::null())
+    .output()
+    .ok()
+    .filter(|o| o.status.success())?;
+  output.stdout.split(|b| *b == b'\n').find_map(Passwd::parse)
+}
+
+fn find_in_file(path: &Path, predicate: impl Fn(&Passwd) -> bool) -> Option<Passwd> {
+  std::fs::read(path)
+    .ok()?
+    .split(|b| *b == b'\n')
+    .filter(|line| !line.starts_with(b"#"))
+    .filter_map(Passwd::parse)
+    .find(predicate)
+}
@@ -0,0 +1,91 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{ffi::CString, os::unix::process::CommandExt, process::Command};
+
+#[cfg(target_vendor = "apple")]
+type Group = libc::c_int;
+#[cfg(not(target_vendor = "apple"))]
+type Group = libc::gid_t;
+
+/// The real and effective ids of the current process.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Ids {
+  pub uid: u32,
+  pub gid: u32,
+  pub euid: u32,
+  pub egid: u32,
+}
+
+impl Ids {
+  pub fn current() -> Self {
+    // SAFETY: these functions have no preconditions and cannot fail.
+    unsafe {
+      Self {
+        uid: libc::getuid(),
+        gid: libc::getgid(),
+        euid: libc::geteuid(),
+        egid: libc::getegid(),
+      }
+    }
+  }
+
+  /// Whether the process runs as root or as a setuid/setgid binary,
+  /// where sourcing the user's startup files could escalate their privileges.
+  pub fn is_elevated(&self) -> bool {
+    self.uid == 0 || self.euid != self.uid || self.egid != self.gid
+  }
+}
+
+/// The groups of a user, its primary group first, with `getgrouplist`.
+///
+/// Falls back to the primary group alone when the lookup fails.
+pub(crate) fn groups(name: &std::ffi::OsStr, gid: u32) -> Vec<libc::gid_t> {
+  use std::os::unix::ffi::OsStrExt;
+
+  let Ok(name) = CString::new(name.as_bytes()) else {
+    return vec![gid];
+  };
+  let mut groups = vec![0 as Group; 32];
+  loop {
+    let mut len = groups.len() as libc::c_int;
+    // SAFETY: `name` is NUL-terminated and `groups` holds `len` entries.
+    let found =
+      unsafe { libc::getgrouplist(name.as_ptr(), gid as _, groups.as_mut_ptr(), &mut len) };
+    if found != -1 {
+      groups.truncate(len.max(0) as usize);
+      // the groups are `c_int` on macOS
+      #[allow(clippy::unnecessary_cast)]
+      return groups
+        .into_iter()
+        .map(|group| group as libc::gid_t)
+        .collect();
+    }
+    // glibc reports the required size, other systems do not
+    let required = (len.max(0) as usize).max(groups.len() * 2);
+    if required > 1 << 16 {
+      return vec![gid];
+    }
+    groups.resize(required, 0);
+  }
+}
+
+/// Makes the command run as the given user, primary group and supplementary groups.
+///
+/// The groups are looked up by the caller, as it is not safe between `fork` and `exec`.
+pub(crate) fn run_as(command: &mut Command, uid: u32, gid: u32, groups: Vec<libc::gid_t>) {
+  // SAFETY: the hook only calls async-signal-safe functions.
+  unsafe {
+    command.pre_exec(move || {
+      // only root can change the supplementary groups
+      if libc::geteuid() == 0 && libc::setgroups(groups.len() as _, groups.as_ptr()) != 0 {
+        return Err(std::io::Error::last_os_error());
+      }
+      if libc::setgid(gid) != 0 || libc::setuid(uid) != 0 {
+        return Err(std::io::Error::last_os_error());
+      }
+      Ok(())
+    });
+  }
+}
@@ -0,0 +1,249 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  io::Read,
+  os::unix::process::CommandExt,
+  process::{Command, ExitStatus, Stdio},
+  sync::mpsc::{channel, RecvTimeoutError},
+  thread,
+  time::{Duration, Instant},
+};
+
+use crate::{CapturedOutput, Error};
+
+/// How long we keep reading after the shell exits.
+///
+/// Daemons started by the shell configuration (ssh-agent, gpg-agent...) can inherit
+/// the output pipes and keep them open forever, so we stop waiting for EOF after this.
+const EXIT_GRACE: Duration = Duration::from_millis(200);
+/// How often we check whether the shell exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub(crate) struct Output {
+  pub status: ExitStatus,
+  pub stdout: Vec<u8>,
+  pub stderr: Vec<u8>,
+}
+
+/// Runs the command in its own session, killing its whole process group if it does not finish before `timeout`.
+///
+/// The stderr attached to [`Error::Timeout`] is redacted with `redact_patterns`.
+pub(crate) fn output(
+  cmd: &mut Command,
+  timeout: Duration,
+  redact_patterns: &[String],
+) -> Result<Output, Error> {
+  let start = Instant::now();
+
+  let mut child = new_session(cmd)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(Error::Shell)?;
+
+  let (tx, rx) = channel();
+  let streams: [Box<dyn Read + Send>; 2] = [
+    Box::new(child.stdout.take().unwrap()),
+    Box::new(child.stderr.take().unwrap()),
+  ];
+  for (index, mut stream) in streams.into_iter().enumerate() {
+    let tx = tx.clone();
+    thread::spawn(move || {
+      let mut buf = [0; 8192];
+      loop {
+        match stream.read(&mut buf) {
+          Ok(0) | Err(_) => {
+            let _ = tx.send((index, None));
+            break;
+          }
+          Ok(n) => {
+            if tx.send((index, Some(buf[..n].to_vec()))).is_err() {
+              break;
+            }
+          }
+        }
+      }
+    });
+  }
+  drop(tx);
+
+  let mut output = [Vec::new(), Vec::new()];
+  let mut open = 2;
+  let mut exited: Option<(ExitStatus, Instant)> = None;
+
+  loop {
+    if exited.is_none() {
+      if let Some(status) = child.try_wait().map_err(Error::Shell)? {
+        exited.replace((status, Instant::now()));
+      } else if start.elapsed() >= timeout {
+        kill_group(child.id());
+        let _ = child.wait();
+        // collect whatever was written before the kill
+        let deadline = Instant::now() + EXIT_GRACE;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+          match rx.recv_timeout(left) {
+            Ok((index, Some(chunk))) => output[index].extend(chunk),
+            Ok((_, None)) => {}
+            Err(_) => break,
+          }
+        }
+        return Err(Error::Timeout {
+          elapsed: start.elapsed(),
+          partial_stderr: CapturedOutput::new(std::mem::take(&mut output[1]), redact_patterns),
+        });
+      }
+    }
+
+    if open == 0 {
+      if let Some((status, _)) = exited {
+        let [stdout, stderr] = output;
+        return Ok(Output {
+          status,
+          stdout,
+          stderr,
+        });
+      }
+    }
+
+    let wait = match exited {
+      Some((_, at)) => {
+        let left = (at + EXIT_GRACE).saturating_duration_since(Instant::now());
+        if left.is_zero() {
+          // the shell is gone but something still holds the pipes open
+          open = 0;
+          continue;
+        }
+        left
+      }
+      None => POLL_INTERVAL,
+    };
+
+    if open == 0 {
+      thread::sleep(wait);
+      continue;
+    }
+
+    match rx.recv_timeout(wait) {
+      Ok((index, Some(chunk))) => output[index].extend(chunk),
+      Ok((_, None)) => open -= 1,
+      Err(RecvTimeoutError::Timeout) => {}
+      Err(RecvTimeoutError::Disconnected) => open = 0,
+    }
+  }
+}
+
+/// Like [`output`] but on the tokio runtime.
+///
+/// Dropping the future kills the process group.
+#[cfg(feature = "tokio")]
+pub(crate) async fn output_async(
+  mut cmd: Command,
+  timeout: Duration,
+  redact_patterns: &[String],
+) -> Result<Output, Error> {
+  use tokio::io::AsyncReadExt;
+
+  let start = Instant::now();
+
+  new_session(&mut cmd)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+  let mut child = tokio::process::Command::from(cmd)
+    .kill_on_drop(true)
+    .spawn()
+    .map_err(Error::Shell)?;
+  let mut group = child.id().map(GroupGuard);
+
+  let mut stdout_pipe = child.stdout.take().unwrap();
+  let mut stderr_pipe = child.stderr.take().unwrap();
+  let mut stdout = Vec::new();
+  let mut stderr = Vec::new();
+  let mut read = Box::pin(async {
+    let _ = tokio::join!(
+      stdout_pipe.read_to_end(&mut stdout),
+      stderr_pipe.read_to_end(&mut stderr)
+    );
+  });
+
+  let deadline = tokio::time::Instant::from_std(start + timeout);
+  let mut read_done = false;
+  let status = loop {
+    tokio::select! {
+      status = child.wait() => break Some(status.map_err(Error::Shell)?),
+      _ = &mut read, if !read_done => read_done = true,
+      _ = tokio::time::sleep_until(deadline) => break None,
+    }
+  };
+
+  // keep collecting what was written, without waiting for daemons holding the pipes open
+  if status.is_none() {
+    drop(group.take());
+    let _ = child.wait().await;
+  }
+  if !read_done {
+    let _ = tokio::time::timeout(EXIT_GRACE, &mut read).await;
+  }
+  drop(read);
+
+  match status {
+    Some(status) => {
+      if let Some(group) = group {
+        group.disarm();
+      }
+      Ok(Output {
+        status,
+        stdout,
+        stderr,
+      })
+    }
+    None => Err(Error::Timeout {
+      elapsed: start.elapsed(),
+      partial_stderr: CapturedOutput::new(stderr, redact_patterns),
+    }),
+  }
+}
+
+/// Kills the process group when dropped, so cancelling the future does not leave the shell behind.
+#[cfg(feature = "tokio")]
+struct GroupGuard(u32);
+
+#[cfg(feature = "tokio")]
+impl GroupGuard {
+  fn disarm(self) {
+    std::mem::forget(self);
+  }
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for GroupGuard {
+  fn drop(&mut self) {
+    kill_group(self.0);
+  }
+}
+
+/// Starts the command in a new session, which also makes it the leader of a new process group.
+///
+/// A mere process group would be in the background of our controlling terminal, if any,
+/// and an interactive shell taking over the terminal would be stopped by `SIGTTOU`.
+fn new_session(cmd: &mut Command) -> &mut Command {
+  // SAFETY: `setsid` is async-signal-safe.
+  unsafe {
+    cmd.pre_exec(|| {
+      if libc::setsid() == -1 {
+        return Err(std::io::Error::last_os_error());
+      }
+      Ok(())
+    })
+  }
+}
+
+fn kill_group(pid: u32) {
+  // SAFETY: the child is the leader of its own process group, created by `setsid` in `new_session`.
+  unsafe {
+    libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+  }
+}
@@ -0,0 +1,159 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::{OsStr, OsString},
+  path::{Path, PathBuf},
+  process::ExitStatus,
+  time::Duration,
+};
+
+use crate::ShellEnv;
+
+/// How a variable of the current process compares to the value resolved from the shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarChange {
+  /// The variable name.
+  pub name: OsString,
+  /// The value before the shell environment was applied, `None` if it was not set.
+  pub old: Option<OsString>,
+  /// The value after the shell value was merged with the old one.
+  pub new: OsString,
+}
+
+impl VarChange {
+  /// Whether the variable was not set before.
+  pub fn is_added(&self) -> bool {
+    self.old.is_none()
+  }
+
+  /// Whether the variable was set to a different value before.
+  pub fn is_changed(&self) -> bool {
+    matches!(&self.old, Some(old) if *old != self.new)
+  }
+
+  /// Whether the variable already had the resolved value.
+  pub fn is_untouched(&self) -> bool {
+    self.old.as_ref() == Some(&self.new)
+  }
+}
+
+/// An entry removed from `PATH` by [`crate::Fixer::sanitize_path`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRemoval {
+  /// The entry as the shell defined it.
+  pub entry: PathBuf,
+  /// Why it was removed.
+  pub reason: PathRemovalReason,
+}
+
+/// Why an entry was removed from `PATH`, see [`PathRemoval`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathRemovalReason {
+  /// The entry is empty, which some shells treat as the current directory.
+  Empty,
+  /// The entry is relative to the current directory, including `~` when the home directory is unknown.
+  Relative,
+  /// The same directory appears earlier.
+  Duplicate,
+  /// The directory does not exist.
+  Missing,
+  /// The directory is writable by everyone.
+  WorldWritable,
+}
+
+/// The result of a [`crate::Fixer::run`] call, describing how the shell was run
+/// and what changed in the environment.
+///
+/// When the variables are not applied to the current process ([`crate::Apply::Skip`]),
+/// the changes describe what applying them would do.
+#[derive(Debug, Clone, Default)]
+pub struct FixReport {
+  pub(crate) env: ShellEnv,
+  pub(crate) changes: Vec<VarChange>,
+  pub(crate) blocked: Vec<(OsString, OsString)>,
+  pub(crate) path_removals: Vec<PathRemoval>,
+  pub(crate) shell: PathBuf,
+  pub(crate) args: Vec<OsString>,
+  pub(crate) status: Option<ExitStatus>,
+  pub(crate) duration: Duration,
+  pub(crate) cached: bool,
+}
+
+impl FixReport {
+  /// The variables resolved from the shell.
+  pub fn env(&self) -> &ShellEnv {
+    &self.env
+  }
+
+  /// Consumes the report, returning the variables resolved from the shell.
+  pub fn into_env(self) -> ShellEnv {
+    self.env
+  }
+
+  /// Every resolved variable compared to the value it had in the current process.
+  pub fn changes(&self) -> &[VarChange] {
+    &self.changes
+  }
+
+  /// The variables that were not set before.
+  pub fn added(&self) -> impl Iterator<Item = &VarChange> {
+    self.changes.iter().filter(|c| c.is_added())
+  }
+
+  /// The variables whose value changed.
+  pub fn changed(&self) -> impl Iterator<Item = &VarChange> {
+    self.changes.iter().filter(|c| c.is_changed())
+  }
+
+  /// The variables that already had the resolved value.
+  pub fn untouched(&self) -> impl Iterator<Item = &VarChange> {
+    self.changes.iter().filter(|c| c.is_untouched())
+  }
+
+  /// The variables the shell defined that were not resolved because they are
+  /// [blocked](crate::Fixer::blocked_vars), with the value they would have had.
+  pub fn blocked(&self) -> impl Iterator<Item = (&OsStr, &OsStr)> {
+    self
+      .blocked
+      .iter()
+      .map(|(k, v)| (k.as_os_str(), v.as_os_str()))
+  }
+
+  /// The entries removed from `PATH` when it is [sanitized](crate::Fixer::sanitize_path).
+  pub fn path_removals(&self) -> &[PathRemoval] {
+    &self.path_removals
+  }
+
+  /// The shell binary that was run.
+  pub fn shell(&self) -> &Path {
+    &self.shell
+  }
+
+  /// The arguments passed to the shell, including the probe command.
+  pub fn args(&self) -> &[OsString] {
+    &self.args
+  }
+
+  /// Whether the environment was loaded from the cache, see [`crate::Fixer::cache`].
+  pub fn cached(&self) -> bool {
+    self.cached
+  }
+
+  /// The exit status of the shell, `None` if it was not run.
+  pub fn status(&self) -> Option<ExitStatus> {
+    self.status
+  }
+
+  /// How long the shell took to print its environment, or to load it from the cache.
+  pub fn duration(&self) -> Duration {
+    self.duration
+  }
+
+  /// Returns the change for the given variable, if the shell resolved it.
+  pub fn change(&self, name: impl AsRef<OsStr>) -> Option<&VarChange> {
+    let name = name.as_ref();
+    self.changes.iter().find(|c| c.name == name)
+  }
+}
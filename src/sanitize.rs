@@ -0,0 +1,72 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::{OsStr, OsString},
+  os::unix::{ffi::OsStrExt, fs::PermissionsExt},
+  path::{Path, PathBuf},
+};
+
+use crate::{fixer::PathOptions, PathRemoval, PathRemovalReason};
+
+/// Cleans up a `PATH` value: expands `~`, removes the empty, relative and duplicated entries
+/// (keeping the first occurrence) and, depending on `options`, the missing and world-writable directories.
+pub(crate) fn sanitize(
+  path: &OsStr,
+  home: Option<&Path>,
+  options: PathOptions,
+) -> (OsString, Vec<PathRemoval>) {
+  let mut entries = Vec::new();
+  let mut removals = Vec::new();
+  for entry in std::env::split_paths(path) {
+    let expanded = expand_tilde(&entry, home);
+    let reason = if entry.as_os_str().is_empty() {
+      Some(PathRemovalReason::Empty)
+    } else if expanded.is_relative() {
+      Some(PathRemovalReason::Relative)
+    } else if entries.contains(&expanded) {
+      Some(PathRemovalReason::Duplicate)
+    } else if options.drop_missing && !expanded.is_dir() {
+      Some(PathRemovalReason::Missing)
+    } else if options.drop_world_writable
+      && std::fs::metadata(&expanded).is_ok_and(|m| m.permissions().mode() & 0o002 != 0)
+    {
+      Some(PathRemovalReason::WorldWritable)
+    } else {
+      None
+    };
+    match reason {
+      Some(reason) => removals.push(PathRemoval { entry, reason }),
+      None => entries.push(expanded),
+    }
+  }
+  // the entries come from `split_paths`, so they cannot contain the separator
+  let path = std::env::join_paths(entries).unwrap_or_else(|_| path.to_os_string());
+  (path, removals)
+}
+
+/// Expands `~` and `~/...` with `home`, and `~user/...` with the home directory of that user.
+fn expand_tilde(entry: &Path, home: Option<&Path>) -> PathBuf {
+  let Some(rest) = entry.as_os_str().as_bytes().strip_prefix(b"~") else {
+    return entry.to_path_buf();
+  };
+  let (user, rest) = match rest.iter().position(|b| *b == b'/') {
+    Some(slash) => (&rest[..slash], &rest[slash + 1..]),
+    None => (rest, &[][..]),
+  };
+  let home = if user.is_empty() {
+    home.map(Path::to_path_buf)
+  } else {
+    std::str::from_utf8(user)
+      .ok()
+      .and_then(crate::passwd::Passwd::from_name)
+      .map(|p| p.home)
+  };
+  match home {
+    Some(home) if rest.is_empty() => home,
+    Some(home) => home.join(OsStr::from_bytes(rest)),
+    // left as is, so it is removed as a relative entry
+    None => entry.to_path_buf(),
+  }
+}
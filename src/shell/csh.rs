@@ -0,0 +1,60 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
+
+use crate::{Delimiter, ShellAdapter};
+
+/// csh and tcsh.
+#[derive(Debug)]
+pub(crate) struct Csh;
+
+impl ShellAdapter for Csh {
+  fn name(&self) -> &str {
+    "csh"
+  }
+
+  fn matches(&self, shell: &Path) -> bool {
+    matches!(super::basename(shell), Some("csh" | "tcsh"))
+  }
+
+  /// csh and tcsh only accept `-l` as their sole argument, so the shell is started with `-c`
+  /// (which reads `.cshrc`/`.tcshrc`) and the script sources the login files itself.
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["-c".into()]
+  }
+
+  /// Sources the login files like a login shell would, then prints the environment between the markers.
+  ///
+  /// csh has no `2>/dev/null`, but only stdout is parsed so the fallbacks do not need it.
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "if ( -r /etc/csh.login ) source /etc/csh.login
+      if ( -r ~/.login ) source ~/.login
+      printf '%s%s' {open_prefix} {open_suffix}
+      env -0 || perl -e 'print \"$_=$ENV{{$_}}\\0\" for keys %ENV' || env
+      printf '%s%s' {close_prefix} {close_suffix}
+      exit"
+    )
+  }
+
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    Ok(crate::parse::parse_env(dump))
+  }
+
+  fn startup_files(&self, home: &Path) -> Vec<PathBuf> {
+    vec![
+      home.join(".cshrc"),
+      home.join(".tcshrc"),
+      home.join(".login"),
+      "/etc/csh.cshrc".into(),
+      "/etc/csh.login".into(),
+    ]
+  }
+}
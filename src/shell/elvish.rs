@@ -0,0 +1,64 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
+
+use crate::{Delimiter, ShellAdapter};
+
+/// elvish.
+#[derive(Debug)]
+pub(crate) struct Elvish;
+
+impl ShellAdapter for Elvish {
+  fn name(&self) -> &str {
+    "elvish"
+  }
+
+  fn matches(&self, shell: &Path) -> bool {
+    super::basename(shell) == Some("elvish")
+  }
+
+  /// elvish has no login mode and only reads `rc.elv` when interactive, so the script evaluates it itself.
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["-c".into()]
+  }
+
+  /// Evaluates the first `rc.elv` found, ignoring its errors since interactive-only modules
+  /// like `edit:` are not available with `-c`, then prints the environment between the markers.
+  ///
+  /// `print` does not add a newline, so each marker is printed in two calls.
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "use path
+      for rc [$E:XDG_CONFIG_HOME/elvish/rc.elv $E:HOME/.config/elvish/rc.elv $E:HOME'/Library/Application Support/elvish/rc.elv' $E:HOME/.elvish/rc.elv] {{
+        if (path:is-regular $rc) {{
+          try {{ eval (slurp < $rc) }} catch {{ }}
+          break
+        }}
+      }}
+      print {open_prefix}; print {open_suffix}
+      try {{ e:env -0 }} catch {{ e:env }}
+      print {close_prefix}; print {close_suffix}"
+    )
+  }
+
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    Ok(crate::parse::parse_env(dump))
+  }
+
+  /// The `rc.elv` candidates the script evaluates.
+  fn startup_files(&self, home: &Path) -> Vec<PathBuf> {
+    vec![
+      super::config_home(home).join("elvish/rc.elv"),
+      home.join(".config/elvish/rc.elv"),
+      home.join("Library/Application Support/elvish/rc.elv"),
+      home.join(".elvish/rc.elv"),
+    ]
+  }
+}
@@ -0,0 +1,84 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  os::unix::ffi::{OsStrExt, OsStringExt},
+  path::{Path, PathBuf},
+};
+
+use crate::{Delimiter, ShellAdapter};
+
+/// Separates the elements of a list variable in the dump.
+const LIST_SEPARATOR: u8 = 0x1e;
+
+/// fish.
+#[derive(Debug)]
+pub(crate) struct Fish;
+
+impl ShellAdapter for Fish {
+  fn name(&self) -> &str {
+    "fish"
+  }
+
+  fn matches(&self, shell: &Path) -> bool {
+    super::basename(shell) == Some("fish")
+  }
+
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["-l".into(), "-i".into(), "-c".into()]
+  }
+
+  /// Prints every exported variable as a NUL-terminated `name=value` record,
+  /// where list elements are separated by the ASCII record separator so we can join them like fish does.
+  ///
+  /// `string collect` keeps multiline values in a single element.
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "printf '%s%s' {open_prefix} {open_suffix}
+      for __fix_path_env_name in (set --names --export)
+        set -l __fix_path_env_value (string join \\x1e -- $$__fix_path_env_name | string collect)
+        string join0 -- \"$__fix_path_env_name=$__fix_path_env_value\"
+      end
+      printf '%s%s' {close_prefix} {close_suffix}
+      exit"
+    )
+  }
+
+  /// Parses the dump, joining list variables the way fish exports them:
+  /// with `:` for path variables (whose name ends in `PATH`) and with spaces for the others.
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    let vars = crate::parse::parse_env(dump)
+      .into_iter()
+      .map(|(name, value)| {
+        let separator: &[u8] = if name.as_bytes().ends_with(b"PATH") {
+          b":"
+        } else {
+          b" "
+        };
+        let value = value
+          .as_bytes()
+          .split(|b| *b == LIST_SEPARATOR)
+          .collect::<Vec<_>>()
+          .join(separator);
+        (name, OsString::from_vec(value))
+      })
+      .collect();
+    Ok(vars)
+  }
+
+  /// fish does not read the POSIX startup files, only its own configuration.
+  fn startup_files(&self, home: &Path) -> Vec<PathBuf> {
+    let config = super::config_home(home).join("fish");
+    vec![
+      config.join("config.fish"),
+      config.join("conf.d"),
+      config.join("fish_variables"),
+      "/etc/fish/config.fish".into(),
+      "/etc/fish/conf.d".into(),
+    ]
+  }
+}
@@ -0,0 +1,154 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The built-in [`ShellAdapter`]s.
+
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use crate::ShellAdapter;
+
+mod csh;
+mod elvish;
+mod fish;
+mod nu;
+mod posix;
+mod pwsh;
+mod xonsh;
+
+/// The built-in adapters, tried in order after the registered ones.
+const BUILTIN: &[&dyn ShellAdapter] = &[
+  &fish::Fish,
+  &nu::Nu,
+  &csh::Csh,
+  &pwsh::Pwsh,
+  &xonsh::Xonsh,
+  &elvish::Elvish,
+];
+
+/// Finds the adapter for the given shell: the first registered one that matches,
+/// then the built-in ones, falling back to the POSIX one.
+pub(crate) fn adapter<'a>(
+  shell: &Path,
+  registered: &'a [Arc<dyn ShellAdapter>],
+) -> &'a dyn ShellAdapter {
+  registered
+    .iter()
+    .map(|adapter| adapter.as_ref())
+    .chain(BUILTIN.iter().copied())
+    .find(|adapter| adapter.matches(shell))
+    .unwrap_or(&posix::Posix)
+}
+
+fn basename(shell: &Path) -> Option<&str> {
+  shell.file_name().and_then(|n| n.to_str())
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config` when it is unset or relative.
+fn config_home(home: &Path) -> PathBuf {
+  std::env::var_os("XDG_CONFIG_HOME")
+    .map(PathBuf::from)
+    .filter(|dir| dir.is_absolute())
+    .unwrap_or_else(|| home.join(".config"))
+}
+
+/// Parses a JSON object dump, for shells that can serialize their environment.
+///
+/// String values are kept as is, lists of strings are joined with `list_separator`
+/// and every other value is dropped as it cannot be exported.
+fn parse_json_object(
+  dump: &[u8],
+  list_separator: &str,
+) -> Result<Vec<(OsString, OsString)>, String> {
+  let vars = match serde_json::from_slice(dump.trim_ascii()) {
+    Ok(serde_json::Value::Object(vars)) => vars,
+    Ok(_) => return Err("expected a JSON object".into()),
+    Err(e) => return Err(e.to_string()),
+  };
+  Ok(
+    vars
+      .into_iter()
+      .filter_map(|(name, value)| {
+        let value = match value {
+          serde_json::Value::String(value) => value,
+          serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .collect::<Vec<_>>()
+            .join(list_separator),
+          _ => return None,
+        };
+        Some((name.into(), value.into()))
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn name(shell: &str) -> String {
+    adapter(Path::new(shell), &[]).name().to_string()
+  }
+
+  #[test]
+  fn csh_routing() {
+    assert_eq!(name("/bin/csh"), "csh");
+    assert_eq!(name("/usr/bin/tcsh"), "csh");
+    assert_eq!(name("/bin/bash"), "posix");
+  }
+
+  #[test]
+  fn pwsh_routing() {
+    assert_eq!(name("/usr/bin/pwsh"), "pwsh");
+    assert_eq!(
+      name("/opt/microsoft/powershell/7-preview/pwsh-preview"),
+      "pwsh"
+    );
+    assert_eq!(name("/usr/local/bin/pwsh-lts"), "pwsh");
+  }
+
+  #[test]
+  fn parse_pwsh_dump() {
+    let dump = br#"
+      {"HOME":"/home/me","MULTILINE":"first\nsecond","LIST":["/a","/b"],"NUMBER":42,"OBJECT":{"a":1}}
+    "#;
+    let mut vars = parse_json_object(dump, ":").unwrap();
+    vars.sort();
+    assert_eq!(
+      vars,
+      [
+        ("HOME".into(), "/home/me".into()),
+        ("LIST".into(), "/a:/b".into()),
+        ("MULTILINE".into(), "first\nsecond".into()),
+      ]
+    );
+    assert!(parse_json_object(b"[]", ":").is_err());
+    assert!(parse_json_object(b"{", ":").is_err());
+  }
+
+  #[test]
+  fn startup_files() {
+    let home = Path::new("/home/me");
+    let files = |shell: &str| adapter(Path::new(shell), &[]).startup_files(home);
+    assert!(files("/bin/bash").contains(&home.join(".profile")));
+    assert!(files("/bin/tcsh").contains(&home.join(".tcshrc")));
+    assert!(!files("/bin/tcsh").contains(&home.join(".profile")));
+    assert!(files("/usr/bin/xonsh").contains(&home.join(".xonshrc")));
+    assert!(files("/usr/bin/pwsh").contains(&home.join(".profile")));
+    assert!(files("/usr/bin/pwsh")
+      .iter()
+      .any(|file| file.ends_with("powershell/Microsoft.PowerShell_profile.ps1")));
+    assert!(files("/usr/bin/fish")
+      .iter()
+      .any(|file| file.ends_with("fish/config.fish")));
+    assert!(files("/usr/bin/elvish")
+      .iter()
+      .all(|file| file.ends_with("rc.elv")));
+  }
+}
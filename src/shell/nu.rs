@@ -0,0 +1,73 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
+
+use crate::{Delimiter, ShellAdapter};
+
+/// Nushell.
+#[derive(Debug)]
+pub(crate) struct Nu;
+
+impl ShellAdapter for Nu {
+  fn name(&self) -> &str {
+    "nu"
+  }
+
+  fn matches(&self, shell: &Path) -> bool {
+    super::basename(shell) == Some("nu")
+  }
+
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["--login".into(), "-c".into()]
+  }
+
+  /// Prints `$env` as a JSON object.
+  ///
+  /// Closures and records (`PROMPT_COMMAND`, `ENV_CONVERSIONS`...) cannot be exported,
+  /// so only strings and lists are kept.
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "print -n ('{open_prefix}' + '{open_suffix}')
+      print -n ($env
+        | transpose key value
+        | where {{|row| ($row.value | describe) == 'string' or (($row.value | describe) starts-with 'list') }}
+        | reduce -f {{}} {{|row, acc| $acc | insert $row.key $row.value }}
+        | to json -r)
+      print -n ('{close_prefix}' + '{close_suffix}')"
+    )
+  }
+
+  /// Parses the JSON object, joining lists (like `PATH`) with `:` and dropping non-string values.
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    super::parse_json_object(dump, ":")
+  }
+
+  /// Every `.nu` file of the configuration directory, like `env.nu`, `config.nu` and `login.nu`.
+  fn startup_files(&self, home: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![super::config_home(home).join("nushell")];
+    if cfg!(target_os = "macos") {
+      dirs.push(home.join("Library/Application Support/nushell"));
+    }
+    let mut files = dirs.clone();
+    for dir in dirs {
+      let Ok(entries) = std::fs::read_dir(dir) else {
+        continue;
+      };
+      files.extend(
+        entries
+          .flatten()
+          .map(|entry| entry.path())
+          .filter(|path| path.extension().is_some_and(|ext| ext == "nu")),
+      );
+    }
+    files.sort();
+    files
+  }
+}
@@ -0,0 +1,43 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{ffi::OsString, path::Path};
+
+use crate::{Delimiter, ShellAdapter};
+
+/// sh, bash, zsh, dash, ksh... and the fallback for unknown shells.
+#[derive(Debug)]
+pub(crate) struct Posix;
+
+impl ShellAdapter for Posix {
+  fn name(&self) -> &str {
+    "posix"
+  }
+
+  fn matches(&self, _shell: &Path) -> bool {
+    true
+  }
+
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["-ilc".into()]
+  }
+
+  /// Prints the environment between the opening and closing markers of `delimiter`.
+  ///
+  /// Records are NUL-terminated so values can contain newlines; `env -0` is not available everywhere
+  /// (busybox, older macOS) so we try perl next and only then fall back to the newline-separated `env`.
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "printf '%s%s' {open_prefix} {open_suffix}; \
+      env -0 2>/dev/null || perl -e 'print \"$_=$ENV{{$_}}\\0\" for keys %ENV' 2>/dev/null || env; \
+      printf '%s%s' {close_prefix} {close_suffix}; exit"
+    )
+  }
+
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    Ok(crate::parse::parse_env(dump))
+  }
+}
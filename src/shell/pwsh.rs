@@ -0,0 +1,61 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
+
+use crate::{Delimiter, ShellAdapter};
+
+/// PowerShell: pwsh, pwsh-preview, pwsh-lts...
+#[derive(Debug)]
+pub(crate) struct Pwsh;
+
+impl ShellAdapter for Pwsh {
+  fn name(&self) -> &str {
+    "pwsh"
+  }
+
+  fn matches(&self, shell: &Path) -> bool {
+    super::basename(shell).is_some_and(|name| name.starts_with("pwsh"))
+  }
+
+  /// `-Login` must be the first argument; it runs `/etc/profile` and `~/.profile` through `/bin/sh`
+  /// before loading `$PROFILE`.
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["-Login".into(), "-NoLogo".into(), "-Command".into()]
+  }
+
+  /// Prints the environment as a JSON object.
+  ///
+  /// `[Console]::Out.Write` is used instead of the output stream so nothing is formatted or wrapped.
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "[Console]::Out.Write('{open_prefix}' + '{open_suffix}')
+      $fixPathEnvVars = [ordered]@{{}}
+      Get-ChildItem env: | ForEach-Object {{ $fixPathEnvVars[$_.Name] = $_.Value }}
+      [Console]::Out.Write(($fixPathEnvVars | ConvertTo-Json -Compress))
+      [Console]::Out.Write('{close_prefix}' + '{close_suffix}')
+      exit"
+    )
+  }
+
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    super::parse_json_object(dump, ":")
+  }
+
+  /// The POSIX files run by `-Login`, then the current user's `$PROFILE` scripts.
+  fn startup_files(&self, home: &Path) -> Vec<PathBuf> {
+    let config = super::config_home(home).join("powershell");
+    let mut files = crate::posix_startup_files(home);
+    files.extend([
+      config.join("profile.ps1"),
+      config.join("Microsoft.PowerShell_profile.ps1"),
+    ]);
+    files
+  }
+}
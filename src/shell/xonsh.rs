@@ -0,0 +1,62 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  ffi::OsString,
+  path::{Path, PathBuf},
+};
+
+use crate::{Delimiter, ShellAdapter};
+
+/// xonsh.
+#[derive(Debug)]
+pub(crate) struct Xonsh;
+
+impl ShellAdapter for Xonsh {
+  fn name(&self) -> &str {
+    "xonsh"
+  }
+
+  fn matches(&self, shell: &Path) -> bool {
+    super::basename(shell) == Some("xonsh")
+  }
+
+  /// xonsh only reads `~/.xonshrc` in interactive mode, even with `-c`.
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["--login".into(), "--interactive".into(), "-c".into()]
+  }
+
+  /// Prints the environment as a JSON object.
+  ///
+  /// `detype()` converts the typed variables (lists, booleans...) to the strings
+  /// xonsh passes to subprocesses, joining paths with `:`.
+  /// The script is Python, so it is kept on a single line to avoid indentation issues.
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "import json, sys; \
+      sys.stdout.write('{open_prefix}' + '{open_suffix}'); \
+      sys.stdout.write(json.dumps(${{...}}.detype())); \
+      sys.stdout.write('{close_prefix}' + '{close_suffix}'); \
+      sys.stdout.flush()"
+    )
+  }
+
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    super::parse_json_object(dump, ":")
+  }
+
+  fn startup_files(&self, home: &Path) -> Vec<PathBuf> {
+    let config = super::config_home(home).join("xonsh");
+    vec![
+      home.join(".xonshrc"),
+      config.join("rc.xsh"),
+      config.join("rc.d"),
+      "/etc/xonsh/xonshrc".into(),
+      "/etc/xonsh/rc.xsh".into(),
+      "/etc/xonsh/rc.d".into(),
+    ]
+  }
+}
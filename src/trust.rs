@@ -0,0 +1,38 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use crate::{Error, UntrustedReason};
+
+/// Checks that the shell is listed in `shells_file` (usually `/etc/shells`)
+/// and that its binary is neither setuid/setgid nor writable by everyone.
+///
+/// Symlinks are resolved, so `/usr/bin/bash` matches a `/bin/bash` entry on merged-usr systems.
+pub(crate) fn check(shell: &Path, shells_file: &Path) -> Result<(), Error> {
+  let binary = fs::canonicalize(shell)?;
+  // a missing or unreadable file lists no shell
+  let listed = fs::read_to_string(shells_file)
+    .unwrap_or_default()
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .any(|entry| {
+      Path::new(entry) == shell || fs::canonicalize(entry).is_ok_and(|entry| entry == binary)
+    });
+  let mode = fs::metadata(&binary)?.permissions().mode();
+  let reason = if !listed {
+    UntrustedReason::NotListed
+  } else if mode & 0o6000 != 0 {
+    UntrustedReason::Setuid
+  } else if mode & 0o002 != 0 {
+    UntrustedReason::WorldWritable
+  } else {
+    return Ok(());
+  };
+  Err(Error::UntrustedShell {
+    shell: shell.to_path_buf(),
+    reason,
+  })
+}
@@ -0,0 +1,82 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{
+  ffi::{OsStr, OsString},
+  path::Path,
+};
+
+use fix_path_env::{Delimiter, ShellAdapter};
+
+/// Probes `/bin/sh` through a binary named `mysh`, printing a single `name value` line.
+#[derive(Debug)]
+struct MySh;
+
+impl ShellAdapter for MySh {
+  fn name(&self) -> &str {
+    "mysh"
+  }
+
+  fn matches(&self, shell: &Path) -> bool {
+    shell.ends_with("mysh")
+  }
+
+  fn command(&self, _shell: &Path) -> std::process::Command {
+    std::process::Command::new("/bin/sh")
+  }
+
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    vec!["-c".into()]
+  }
+
+  fn envs(&self, _shell: &Path) -> Vec<(OsString, OsString)> {
+    vec![("MYSH_VALUE".into(), "from adapter".into())]
+  }
+
+  fn script(&self, delimiter: &Delimiter) -> String {
+    let (open_prefix, open_suffix) = delimiter.open_halves();
+    let (close_prefix, close_suffix) = delimiter.close_halves();
+    format!(
+      "printf '%s%s' {open_prefix} {open_suffix}; \
+      printf 'MYSH %s' \"$MYSH_VALUE\"; \
+      printf '%s%s' {close_prefix} {close_suffix}"
+    )
+  }
+
+  fn parse(&self, dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    let dump = std::str::from_utf8(dump).map_err(|e| e.to_string())?;
+    let (name, value) = dump.split_once(' ').ok_or("missing separator")?;
+    Ok(vec![(name.into(), value.into())])
+  }
+}
+
+#[test]
+fn registered_adapter() {
+  let report = common::fixer()
+    .shell("/opt/bin/mysh")
+    .adapter(MySh)
+    .run()
+    .unwrap();
+  assert_eq!(report.env().len(), 1);
+  assert_eq!(report.env().get("MYSH"), Some(OsStr::new("from adapter")));
+  assert_eq!(report.args()[0], "-c");
+}
+
+#[test]
+fn unmatched_adapter_falls_back_to_builtin() {
+  let report = common::sh()
+    .env("MYSH_VALUE", "from fixer")
+    .adapter(MySh)
+    .run()
+    .unwrap();
+  assert_eq!(
+    report.env().get("MYSH_VALUE"),
+    Some(OsStr::new("from fixer"))
+  );
+  assert!(!report.env().contains_key("MYSH"));
+}
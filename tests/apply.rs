@@ -0,0 +1,144 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{
+  ffi::{OsStr, OsString},
+  path::Path,
+};
+
+use fix_path_env::{Delimiter, MergePolicy, ShellAdapter};
+
+fn merge(policy: MergePolicy, current: Option<&str>) -> OsString {
+  policy.merge(
+    current.map(OsStr::new),
+    OsStr::new("/usr/local/bin:/app/bin:/usr/bin"),
+  )
+}
+
+#[test]
+fn merge_policies() {
+  let current = Some("/app/bin:/bin");
+  assert_eq!(
+    merge(MergePolicy::Replace, current),
+    "/usr/local/bin:/app/bin:/usr/bin"
+  );
+  assert_eq!(
+    merge(MergePolicy::Prepend, current),
+    "/usr/local/bin:/app/bin:/usr/bin:/app/bin:/bin"
+  );
+  assert_eq!(
+    merge(MergePolicy::Append, current),
+    "/app/bin:/bin:/usr/local/bin:/app/bin:/usr/bin"
+  );
+  assert_eq!(merge(MergePolicy::KeepExisting, current), "/app/bin:/bin");
+  assert_eq!(
+    merge(MergePolicy::Union, current),
+    "/app/bin:/bin:/usr/local/bin:/usr/bin"
+  );
+}
+
+#[test]
+fn merge_policies_without_current_value() {
+  for policy in [
+    MergePolicy::Replace,
+    MergePolicy::Prepend,
+    MergePolicy::Append,
+    MergePolicy::KeepExisting,
+    MergePolicy::Union,
+  ] {
+    assert_eq!(merge(policy, None), "/usr/local/bin:/app/bin:/usr/bin");
+    assert_eq!(merge(policy, Some("")), "/usr/local/bin:/app/bin:/usr/bin");
+  }
+}
+
+#[test]
+fn command_with_shell_env() {
+  use fix_path_env::CommandExt;
+
+  let env = common::sh()
+    .env("FIX_PATH_ENV_LIST", "/shell:/common")
+    .env("FIX_PATH_ENV_KEEP", "shell")
+    .vars(&["FIX_PATH_ENV_LIST", "FIX_PATH_ENV_KEEP"])
+    .merge_policy("FIX_PATH_ENV_LIST", MergePolicy::Union)
+    .merge_policy("FIX_PATH_ENV_KEEP", MergePolicy::KeepExisting)
+    .run()
+    .unwrap()
+    .into_env();
+
+  let out = std::process::Command::new("/bin/sh")
+    .args([
+      "-c",
+      "printf '%s %s' \"$FIX_PATH_ENV_LIST\" \"$FIX_PATH_ENV_KEEP\"",
+    ])
+    .env("FIX_PATH_ENV_LIST", "/app:/common")
+    .env("FIX_PATH_ENV_KEEP", "app")
+    .with_shell_env(&env)
+    .output()
+    .unwrap();
+  assert_eq!(
+    String::from_utf8_lossy(&out.stdout),
+    "/app:/common:/shell app"
+  );
+  assert!(std::env::var_os("FIX_PATH_ENV_LIST").is_none());
+}
+
+#[test]
+fn background_apply_once() {
+  let handle = common::sh()
+    .env("FIX_PATH_ENV_BACKGROUND", "/shell")
+    .vars(&["FIX_PATH_ENV_BACKGROUND"])
+    .merge_policy("FIX_PATH_ENV_BACKGROUND", MergePolicy::Append)
+    .spawn();
+  assert!(handle.wait().is_ok());
+  assert!(handle.try_get().is_some());
+  assert!(std::env::var_os("FIX_PATH_ENV_BACKGROUND").is_none());
+
+  std::thread::scope(|s| {
+    for _ in 0..4 {
+      s.spawn(|| handle.apply().unwrap());
+    }
+  });
+  assert_eq!(handle.apply().unwrap().len(), 1);
+  assert_eq!(std::env::var("FIX_PATH_ENV_BACKGROUND").unwrap(), "/shell");
+}
+
+/// Panics while probing, on the thread running it.
+#[derive(Debug)]
+struct Broken;
+
+impl ShellAdapter for Broken {
+  fn name(&self) -> &str {
+    "broken"
+  }
+
+  fn matches(&self, _shell: &Path) -> bool {
+    true
+  }
+
+  fn args(&self, _shell: &Path) -> Vec<OsString> {
+    Vec::new()
+  }
+
+  fn script(&self, _delimiter: &Delimiter) -> String {
+    panic!("broken adapter")
+  }
+
+  fn parse(&self, _dump: &[u8]) -> Result<Vec<(OsString, OsString)>, String> {
+    unreachable!()
+  }
+}
+
+#[test]
+fn panicking_probe_thread() {
+  let handle = common::sh().adapter(Broken).spawn();
+  for _ in 0..2 {
+    let err = handle.wait().unwrap_err();
+    assert!(err.to_string().contains("broken adapter"), "{err}");
+  }
+  assert!(handle.apply().is_err());
+}
@@ -0,0 +1,71 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(all(not(windows), feature = "tokio"))]
+
+mod common;
+
+use std::{
+  ffi::OsStr,
+  fs,
+  time::{Duration, Instant},
+};
+
+use fix_path_env::Error;
+
+#[tokio::test]
+async fn run_async() {
+  let report = common::sh()
+    .env("MULTILINE", "first\nsecond")
+    .run_async()
+    .await
+    .unwrap();
+  assert_eq!(
+    report.env().get("MULTILINE"),
+    Some(OsStr::new("first\nsecond"))
+  );
+}
+
+#[tokio::test]
+async fn run_async_timeout() {
+  let err = common::sh()
+    .args(["-c", "echo waiting >&2; sleep 30; eval \"$0\""])
+    .timeout(Duration::from_millis(500))
+    .run_async()
+    .await
+    .unwrap_err();
+  match err {
+    Error::Timeout { partial_stderr, .. } => assert_eq!(partial_stderr.redacted(), "waiting\n"),
+    e => panic!("unexpected error {e}"),
+  }
+}
+
+#[tokio::test]
+async fn run_async_cancelled() {
+  let dir = tempfile::tempdir().unwrap();
+  let pid_file = dir.path().join("pid");
+  let fixer = common::sh().args([
+    "-c".to_string(),
+    format!("echo $$ > '{}'; sleep 30; eval \"$0\"", pid_file.display()),
+  ]);
+  // dropping the future before the probe finishes must not leave the shell behind
+  tokio::time::timeout(Duration::from_millis(500), fixer.run_async())
+    .await
+    .unwrap_err();
+
+  let pid: libc::pid_t = fs::read_to_string(&pid_file)
+    .unwrap()
+    .trim()
+    .parse()
+    .unwrap();
+  let start = Instant::now();
+  // SAFETY: the signal 0 only checks whether the process exists.
+  while unsafe { libc::kill(pid, 0) } == 0 {
+    assert!(
+      start.elapsed() < Duration::from_secs(5),
+      "shell still running"
+    );
+    tokio::time::sleep(Duration::from_millis(20)).await;
+  }
+}
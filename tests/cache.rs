@@ -0,0 +1,100 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{
+  ffi::OsStr,
+  fs,
+  os::unix::fs::{MetadataExt, PermissionsExt},
+  time::{Duration, Instant},
+};
+
+#[test]
+fn cache_hit() {
+  let dir = tempfile::tempdir().unwrap();
+  let fixer = common::sh()
+    .env("CACHED", "value")
+    .vars(&["CACHED"])
+    .cache(true)
+    .cache_dir(dir.path());
+
+  let first = fixer.run().unwrap();
+  assert!(!first.cached());
+
+  let entries = fs::read_dir(dir.path())
+    .unwrap()
+    .map(|e| e.unwrap())
+    .collect::<Vec<_>>();
+  assert_eq!(entries.len(), 1);
+  assert_eq!(
+    entries[0].metadata().unwrap().permissions().mode() & 0o777,
+    0o600
+  );
+
+  let second = fixer.run().unwrap();
+  assert!(second.cached());
+  assert_eq!(second.env(), first.env());
+  assert_eq!(second.env().get("CACHED"), Some(OsStr::new("value")));
+
+  // a different configuration does not use the entry
+  let other = fixer.env("CACHED", "other").run().unwrap();
+  assert!(!other.cached());
+  assert_eq!(other.env().get("CACHED"), Some(OsStr::new("other")));
+}
+
+#[test]
+fn refresh_old_entry() {
+  let dir = tempfile::tempdir().unwrap();
+  let fixer = common::sh()
+    .vars(&["PATH"])
+    .cache(true)
+    .cache_dir(dir.path())
+    .cache_max_age(Duration::ZERO);
+  assert!(!fixer.run().unwrap().cached());
+
+  let entry = || {
+    let entry = fs::read_dir(dir.path())
+      .unwrap()
+      .map(|e| e.unwrap())
+      .find(|e| e.path().extension().is_none())
+      .unwrap();
+    entry.metadata().unwrap().ino()
+  };
+  let stored = entry();
+  // the outdated entry is still used, and rewritten on a background thread
+  assert!(fixer.run().unwrap().cached());
+  let start = Instant::now();
+  while entry() == stored {
+    assert!(start.elapsed() < Duration::from_secs(10), "not refreshed");
+    std::thread::sleep(Duration::from_millis(20));
+  }
+}
+
+#[test]
+fn options_do_not_enable_cache() {
+  let dir = tempfile::tempdir().unwrap();
+  let fixer = common::sh()
+    .vars(&["PATH"])
+    .cache_dir(dir.path())
+    .cache_max_age(Duration::ZERO);
+  assert!(!fixer.run().unwrap().cached());
+  assert!(!fixer.run().unwrap().cached());
+  assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+#[ignore = "requires root"]
+fn elevated_process_skips_cache() {
+  let dir = tempfile::tempdir().unwrap();
+  let fixer = common::sh()
+    .vars(&["PATH"])
+    .cache(true)
+    .cache_dir(dir.path());
+  assert!(!fixer.run().unwrap().cached());
+  assert!(!fixer.run().unwrap().cached());
+  assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+}
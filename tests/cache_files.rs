@@ -0,0 +1,26 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::fs;
+
+// the only test of this binary, so changing HOME cannot race with another one
+#[test]
+fn startup_file_invalidates_entry() {
+  let home = tempfile::tempdir().unwrap();
+  std::env::set_var("HOME", home.path());
+  let fixer = common::sh()
+    .vars(&["PATH"])
+    .cache(true)
+    .cache_dir(home.path().join("cache"));
+
+  assert!(!fixer.run().unwrap().cached());
+  assert!(fixer.run().unwrap().cached());
+
+  fs::write(home.path().join(".profile"), "export EDITED=1\n").unwrap();
+  assert!(!fixer.run().unwrap().cached());
+}
@@ -0,0 +1,53 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+// every test binary uses a different subset of the helpers
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use fix_path_env::{Apply, Fixer, PrivilegePolicy};
+
+/// A fixer leaving the test process untouched.
+///
+/// The tests may run as root in containers, so the privilege policy is relaxed.
+pub fn fixer() -> Fixer {
+  Fixer::new()
+    .privilege_policy(PrivilegePolicy::Allow)
+    .apply(Apply::Skip)
+}
+
+/// Runs `/bin/sh -c` without the startup files.
+pub fn sh() -> Fixer {
+  fixer().shell("/bin/sh").args(["-c"])
+}
+
+/// Finds a program on `PATH`.
+pub fn find(program: &str) -> Option<PathBuf> {
+  std::env::split_paths(&std::env::var_os("PATH")?)
+    .map(|dir| dir.join(program))
+    .find(|path| path.is_file())
+}
+
+/// Finds a program the test cannot run without, as the shell-specific tests are ignored
+/// unless the shell is installed.
+pub fn require(program: &str) -> PathBuf {
+  find(program).unwrap_or_else(|| panic!("{program} is not installed"))
+}
+
+/// The output of `id` with the given flag, like `-u`.
+pub fn id(flag: &str) -> String {
+  let output = std::process::Command::new("id").arg(flag).output().unwrap();
+  String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// The login shell of a user name or id, the last field of its passwd entry.
+pub fn login_shell(user: &str) -> PathBuf {
+  let output = std::process::Command::new("getent")
+    .args(["passwd", user])
+    .output()
+    .unwrap();
+  let entry = String::from_utf8(output.stdout).unwrap();
+  entry.trim_end().rsplit(':').next().unwrap().into()
+}
@@ -0,0 +1,35 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{ffi::OsStr, fs, path::Path};
+
+#[test]
+#[ignore = "requires tcsh"]
+fn tcsh_startup_files() {
+  let tcsh = common::require("tcsh");
+
+  let home = tempfile::tempdir().unwrap();
+  let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/csh");
+  fs::copy(fixtures.join("cshrc"), home.path().join(".cshrc")).unwrap();
+  fs::copy(fixtures.join("login"), home.path().join(".login")).unwrap();
+
+  let report = common::fixer()
+    .shell(tcsh)
+    .env("HOME", home.path())
+    .run()
+    .unwrap();
+  let env = report.env();
+
+  assert_eq!(env.get("FIXTURE_CSHRC"), Some(OsStr::new("from cshrc")));
+  assert!(env
+    .get("PATH")
+    .unwrap()
+    .to_str()
+    .unwrap()
+    .starts_with("/fixture/bin:"));
+}
@@ -0,0 +1,90 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{
+  ffi::OsStr,
+  fs,
+  os::unix::{ffi::OsStrExt, fs::PermissionsExt},
+};
+
+#[test]
+fn nul_dump_keeps_multiline_values() {
+  let report = common::sh()
+    .env("MULTILINE", "first\nSECOND=bogus\nthird")
+    .env("AFTER", "value")
+    .run()
+    .unwrap();
+  let env = report.env();
+  assert_eq!(
+    env.get("MULTILINE"),
+    Some(OsStr::new("first\nSECOND=bogus\nthird"))
+  );
+  assert_eq!(env.get("AFTER"), Some(OsStr::new("value")));
+  assert!(!env.contains_key("SECOND"));
+}
+
+#[test]
+fn newline_dump_fallback() {
+  // an `env` that does not understand `-0`, and no perl on PATH
+  let bin = tempfile::tempdir().unwrap();
+  let env = bin.path().join("env");
+  fs::write(
+    &env,
+    "#!/bin/sh\n[ \"$1\" = -0 ] && exit 1\nexec /usr/bin/env \"$@\"\n",
+  )
+  .unwrap();
+  fs::set_permissions(&env, fs::Permissions::from_mode(0o755)).unwrap();
+
+  let report = common::sh()
+    .env("PATH", bin.path())
+    .env("SINGLE", "value")
+    .env("MULTILINE", "first\nSECOND=bogus")
+    .run()
+    .unwrap();
+  let env = report.env();
+  assert_eq!(env.get("SINGLE"), Some(OsStr::new("value")));
+  assert_eq!(env.get("PATH"), Some(bin.path().as_os_str()));
+  // the last-resort format cannot represent newlines
+  assert_eq!(env.get("MULTILINE"), Some(OsStr::new("first")));
+  assert_eq!(env.get("SECOND"), Some(OsStr::new("bogus")));
+}
+
+#[test]
+fn non_utf8_values_are_preserved() {
+  let value = OsStr::from_bytes(b"/opt/caf\xe9/bin");
+  let report = common::sh().env("LEGACY", value).run().unwrap();
+  assert_eq!(report.env().get("LEGACY"), Some(value));
+}
+
+#[test]
+fn escape_sequences_are_stripped() {
+  let report = common::sh()
+    .env("COLORED", "\x1b[31mred\x1b[0m")
+    .run()
+    .unwrap();
+  assert_eq!(report.env().get("COLORED"), Some(OsStr::new("red")));
+}
+
+#[test]
+fn all_vars_skips_session_variables() {
+  let report = common::sh().env("KEPT", "value").run().unwrap();
+  let env = report.env();
+  assert_eq!(env.get("KEPT"), Some(OsStr::new("value")));
+  assert!(!env.contains_key("PWD"));
+  assert!(!env.contains_key("DISABLE_AUTO_UPDATE"));
+
+  let report = common::sh()
+    .exclude(&["KEPT"])
+    .env("KEPT", "value")
+    .run()
+    .unwrap();
+  assert!(!report.env().contains_key("KEPT"));
+
+  let report = common::sh().excluded_vars(["KEPT"]).run().unwrap();
+  assert!(report.env().contains_key("PWD"));
+}
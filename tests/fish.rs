@@ -0,0 +1,51 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{ffi::OsStr, fs};
+
+#[test]
+#[ignore = "requires fish"]
+fn fish_config() {
+  let fish = common::require("fish");
+
+  let config = tempfile::tempdir().unwrap();
+  let data = tempfile::tempdir().unwrap();
+  fs::create_dir(config.path().join("fish")).unwrap();
+  fs::copy(
+    concat!(
+      env!("CARGO_MANIFEST_DIR"),
+      "/tests/fixtures/fish/config.fish"
+    ),
+    config.path().join("fish/config.fish"),
+  )
+  .unwrap();
+
+  let report = common::fixer()
+    .shell(fish)
+    .env("XDG_CONFIG_HOME", config.path())
+    .env("XDG_DATA_HOME", data.path())
+    .run()
+    .unwrap();
+  let env = report.env();
+
+  assert_eq!(env.get("FIXTURE_LIST"), Some(OsStr::new("one two three")));
+  assert_eq!(
+    env.get("FIXTURE_PATH"),
+    Some(OsStr::new("/fixture/a:/fixture/b"))
+  );
+  assert_eq!(
+    env.get("FIXTURE_MULTILINE"),
+    Some(OsStr::new("first\nsecond"))
+  );
+  assert!(env
+    .get("PATH")
+    .unwrap()
+    .to_str()
+    .unwrap()
+    .starts_with("/fixture/bin:"));
+}
@@ -0,0 +1,5 @@
+set -gx FIXTURE_LIST one two three
+set -gx FIXTURE_PATH /fixture/a /fixture/b
+set -gx FIXTURE_MULTILINE "first
+second"
+set -gx PATH /fixture/bin $PATH
@@ -0,0 +1,3 @@
+$env:FIXTURE_PROFILE = 'from profile'
+$env:FIXTURE_MULTILINE = "first`nsecond"
+$env:PATH = '/fixture/bin:' + $env:PATH
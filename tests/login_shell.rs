@@ -0,0 +1,15 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+// the only test of this binary, so changing SHELL cannot race with another one
+#[test]
+fn bogus_shell_falls_back_to_passwd() {
+  std::env::set_var("SHELL", "/nonexistent/shell");
+  let report = common::fixer().args(["-c"]).vars(&["PATH"]).run().unwrap();
+  assert_eq!(report.shell(), common::login_shell(&common::id("-u")));
+}
@@ -0,0 +1,50 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{ffi::OsStr, fs, path::PathBuf};
+
+#[test]
+#[ignore = "requires nu"]
+fn nu_config() {
+  let nu = common::require("nu");
+
+  let config = tempfile::tempdir().unwrap();
+  let nushell = config.path().join("nushell");
+  fs::create_dir(&nushell).unwrap();
+  for file in ["env.nu", "config.nu"] {
+    fs::copy(
+      PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/nushell")
+        .join(file),
+      nushell.join(file),
+    )
+    .unwrap();
+  }
+
+  let report = common::fixer()
+    .shell(nu)
+    .env("XDG_CONFIG_HOME", config.path())
+    .run()
+    .unwrap();
+  let env = report.env();
+
+  assert_eq!(env.get("FIXTURE_STRING"), Some(OsStr::new("value")));
+  assert_eq!(env.get("FIXTURE_CONFIG"), Some(OsStr::new("config")));
+  assert_eq!(
+    env.get("FIXTURE_MULTILINE"),
+    Some(OsStr::new("first\nsecond"))
+  );
+  assert!(!env.contains_key("FIXTURE_NUMBER"));
+  assert!(!env.contains_key("FIXTURE_RECORD"));
+  assert!(env
+    .get("PATH")
+    .unwrap()
+    .to_str()
+    .unwrap()
+    .starts_with("/fixture/bin:"));
+}
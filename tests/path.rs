@@ -0,0 +1,95 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use fix_path_env::{Fixer, PathRemovalReason};
+
+fn fixer(home: &Path, path: &str) -> Fixer {
+  common::sh()
+    .env("HOME", home)
+    .env("PATH", path)
+    .vars(&["HOME", "PATH"])
+}
+
+fn removals(report: &fix_path_env::FixReport) -> Vec<(&str, PathRemovalReason)> {
+  report
+    .path_removals()
+    .iter()
+    .map(|r| (r.entry.to_str().unwrap(), r.reason))
+    .collect()
+}
+
+#[test]
+fn sanitize_path() {
+  let home = tempfile::tempdir().unwrap();
+  let report = fixer(
+    home.path(),
+    "~/bin:.:/usr/bin::/usr/bin/:bin:/nonexistent:/bin",
+  )
+  .sanitize_path(true)
+  .run()
+  .unwrap();
+  assert_eq!(
+    report.env().get("PATH").unwrap(),
+    format!("{}/bin:/usr/bin:/nonexistent:/bin", home.path().display()).as_str()
+  );
+  assert_eq!(
+    removals(&report),
+    [
+      (".", PathRemovalReason::Relative),
+      ("", PathRemovalReason::Empty),
+      ("/usr/bin/", PathRemovalReason::Duplicate),
+      ("bin", PathRemovalReason::Relative),
+    ]
+  );
+}
+
+#[test]
+fn drop_missing_and_world_writable() {
+  let home = tempfile::tempdir().unwrap();
+  let shared = home.path().join("shared");
+  fs::create_dir(&shared).unwrap();
+  fs::set_permissions(&shared, fs::Permissions::from_mode(0o777)).unwrap();
+
+  let path = format!("/usr/bin:/nonexistent:{}:/bin", shared.display());
+  let report = fixer(home.path(), &path)
+    .sanitize_path(true)
+    .drop_missing_path_dirs(true)
+    .drop_world_writable_path_dirs(true)
+    .run()
+    .unwrap();
+  assert_eq!(report.env().get("PATH").unwrap(), "/usr/bin:/bin");
+  assert_eq!(
+    removals(&report),
+    [
+      ("/nonexistent", PathRemovalReason::Missing),
+      (shared.to_str().unwrap(), PathRemovalReason::WorldWritable),
+    ]
+  );
+}
+
+#[test]
+fn disabled_by_default() {
+  let home = tempfile::tempdir().unwrap();
+  let report = fixer(home.path(), "/usr/bin:.:/bin").run().unwrap();
+  assert_eq!(report.env().get("PATH").unwrap(), "/usr/bin:.:/bin");
+  assert!(report.path_removals().is_empty());
+}
+
+#[test]
+fn options_do_not_enable_sanitization() {
+  let home = tempfile::tempdir().unwrap();
+  let report = fixer(home.path(), "/usr/bin:.:/nonexistent")
+    .drop_missing_path_dirs(true)
+    .drop_world_writable_path_dirs(false)
+    .run()
+    .unwrap();
+  assert_eq!(report.env().get("PATH").unwrap(), "/usr/bin:.:/nonexistent");
+  assert!(report.path_removals().is_empty());
+}
@@ -0,0 +1,60 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::ffi::OsStr;
+
+use fix_path_env::{FixReport, Fixer};
+
+fn fixer() -> Fixer {
+  common::sh()
+    .env("DYLD_INSERT_LIBRARIES", "/tmp/inject.dylib")
+    .env("NODE_OPTIONS", "--require /tmp/inject.js")
+    .env("FIX_PATH_ENV_SAFE", "value")
+    // cargo sets LD_LIBRARY_PATH for the tests
+    .vars(&["DYLD_INSERT_LIBRARIES", "NODE_OPTIONS", "FIX_PATH_ENV_SAFE"])
+}
+
+fn blocked(report: &FixReport) -> Vec<&OsStr> {
+  let mut names = report.blocked().map(|(name, _)| name).collect::<Vec<_>>();
+  names.sort();
+  names
+}
+
+#[test]
+fn blocked_by_default() {
+  let report = fixer().run().unwrap();
+  assert!(!report.env().contains_key("DYLD_INSERT_LIBRARIES"));
+  assert!(!report.env().contains_key("NODE_OPTIONS"));
+  assert!(report.env().contains_key("FIX_PATH_ENV_SAFE"));
+  assert_eq!(blocked(&report), ["DYLD_INSERT_LIBRARIES", "NODE_OPTIONS"]);
+}
+
+#[test]
+fn explicitly_allowed() {
+  let report = fixer().allow(&["NODE_OPTIONS"]).run().unwrap();
+  assert_eq!(
+    report.env().get("NODE_OPTIONS"),
+    Some(OsStr::new("--require /tmp/inject.js"))
+  );
+  assert_eq!(blocked(&report), ["DYLD_INSERT_LIBRARIES"]);
+}
+
+#[test]
+fn blocked_from_cache() {
+  let dir = tempfile::tempdir().unwrap();
+  let fixer = fixer()
+    .vars(&["NODE_OPTIONS"])
+    .cache(true)
+    .cache_dir(dir.path());
+  assert_eq!(blocked(&fixer.run().unwrap()), ["NODE_OPTIONS"]);
+
+  let cached = fixer.run().unwrap();
+  assert!(cached.cached());
+  assert!(cached.env().is_empty());
+  assert_eq!(blocked(&cached), ["NODE_OPTIONS"]);
+}
@@ -0,0 +1,49 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use fix_path_env::{Error, Fixer, PrivilegePolicy};
+
+fn fixer() -> Fixer {
+  common::sh()
+    .privilege_policy(PrivilegePolicy::Refuse)
+    .vars(&["PATH"])
+}
+
+#[test]
+fn user_policies() {
+  for policy in [
+    PrivilegePolicy::Refuse,
+    PrivilegePolicy::Skip,
+    PrivilegePolicy::DropToRealUser,
+  ] {
+    let report = fixer().privilege_policy(policy).run().unwrap();
+    assert!(report.env().contains_key("PATH"));
+    assert!(report.status().unwrap().success());
+  }
+}
+
+#[test]
+#[ignore = "requires root"]
+fn root_policies() {
+  assert!(matches!(
+    fixer().run(),
+    Err(Error::Privileged { uid: 0, .. })
+  ));
+  assert!(matches!(
+    fixer()
+      .privilege_policy(PrivilegePolicy::DropToRealUser)
+      .run(),
+    Err(Error::Privileged { uid: 0, .. })
+  ));
+  let report = fixer()
+    .privilege_policy(PrivilegePolicy::Skip)
+    .run()
+    .unwrap();
+  assert!(report.env().is_empty());
+  assert!(report.status().is_none());
+}
@@ -0,0 +1,46 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{ffi::OsStr, fs, path::Path};
+
+#[test]
+#[ignore = "requires pwsh"]
+fn pwsh_profile() {
+  let pwsh = common::require("pwsh");
+
+  let config = tempfile::tempdir().unwrap();
+  let powershell = config.path().join("powershell");
+  fs::create_dir(&powershell).unwrap();
+  let profile = "Microsoft.PowerShell_profile.ps1";
+  fs::copy(
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+      .join("tests/fixtures/powershell")
+      .join(profile),
+    powershell.join(profile),
+  )
+  .unwrap();
+
+  let report = common::fixer()
+    .shell(pwsh)
+    .env("XDG_CONFIG_HOME", config.path())
+    .run()
+    .unwrap();
+  let env = report.env();
+
+  assert_eq!(env.get("FIXTURE_PROFILE"), Some(OsStr::new("from profile")));
+  assert_eq!(
+    env.get("FIXTURE_MULTILINE"),
+    Some(OsStr::new("first\nsecond"))
+  );
+  assert!(env
+    .get("PATH")
+    .unwrap()
+    .to_str()
+    .unwrap()
+    .starts_with("/fixture/bin:"));
+}
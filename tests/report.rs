@@ -0,0 +1,63 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::ffi::OsStr;
+
+use fix_path_env::Apply;
+
+// the only test of this binary, so changing the environment cannot race with another one
+#[test]
+fn applied_changes() {
+  std::env::remove_var("FIX_PATH_ENV_ADDED");
+  std::env::set_var("FIX_PATH_ENV_CHANGED", "old");
+  std::env::set_var("FIX_PATH_ENV_SAME", "same");
+
+  let report = common::sh()
+    .env("FIX_PATH_ENV_ADDED", "added")
+    .env("FIX_PATH_ENV_CHANGED", "new")
+    .env("FIX_PATH_ENV_SAME", "same")
+    .vars(&[
+      "FIX_PATH_ENV_ADDED",
+      "FIX_PATH_ENV_CHANGED",
+      "FIX_PATH_ENV_SAME",
+    ])
+    .apply(Apply::Process)
+    .run()
+    .unwrap();
+
+  let names = |changes: Vec<&fix_path_env::VarChange>| {
+    changes
+      .into_iter()
+      .map(|c| c.name.clone())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(names(report.added().collect()), ["FIX_PATH_ENV_ADDED"]);
+  assert_eq!(names(report.changed().collect()), ["FIX_PATH_ENV_CHANGED"]);
+  assert_eq!(names(report.untouched().collect()), ["FIX_PATH_ENV_SAME"]);
+
+  let added = report.change("FIX_PATH_ENV_ADDED").unwrap();
+  assert!(added.is_added());
+  assert_eq!(added.old, None);
+  assert_eq!(added.new, "added");
+  let changed = report.change("FIX_PATH_ENV_CHANGED").unwrap();
+  assert!(changed.is_changed());
+  assert_eq!(changed.old.as_deref(), Some(OsStr::new("old")));
+  assert_eq!(changed.new, "new");
+  let same = report.change("FIX_PATH_ENV_SAME").unwrap();
+  assert!(same.is_untouched());
+  assert_eq!(same.old.as_deref(), Some(OsStr::new("same")));
+  assert_eq!(same.new, "same");
+
+  assert_eq!(std::env::var("FIX_PATH_ENV_ADDED").unwrap(), "added");
+  assert_eq!(std::env::var("FIX_PATH_ENV_CHANGED").unwrap(), "new");
+
+  // the shell actually ran
+  assert!(!report.cached());
+  assert!(report.status().is_some_and(|status| status.success()));
+  assert!(report.duration() > std::time::Duration::ZERO);
+}
@@ -0,0 +1,69 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use fix_path_env::{Error, UntrustedReason};
+
+fn run(shell: &Path, shells_file: &Path) -> Result<(), Error> {
+  common::sh()
+    .shell(shell)
+    .vars(&["PATH"])
+    .strict(true)
+    .shells_file(shells_file)
+    .run()
+    .map(|_| ())
+}
+
+fn reason(result: Result<(), Error>) -> UntrustedReason {
+  match result {
+    Err(Error::UntrustedShell { reason, .. }) => reason,
+    other => panic!("expected an untrusted shell error, got {other:?}"),
+  }
+}
+
+#[test]
+fn listed_shell() {
+  let dir = tempfile::tempdir().unwrap();
+  let shells = dir.path().join("shells");
+  fs::write(&shells, "# comment\n/bin/sh\n").unwrap();
+  run(Path::new("/bin/sh"), &shells).unwrap();
+
+  fs::write(&shells, "/bin/bash\n").unwrap();
+  assert_eq!(
+    reason(run(Path::new("/bin/sh"), &shells)),
+    UntrustedReason::NotListed
+  );
+}
+
+#[test]
+fn unsafe_permissions() {
+  let dir = tempfile::tempdir().unwrap();
+  let shell = dir.path().join("sh");
+  fs::copy("/bin/sh", &shell).unwrap();
+  let shells = dir.path().join("shells");
+  fs::write(&shells, format!("{}\n", shell.display())).unwrap();
+
+  fs::set_permissions(&shell, fs::Permissions::from_mode(0o757)).unwrap();
+  assert_eq!(reason(run(&shell, &shells)), UntrustedReason::WorldWritable);
+
+  fs::set_permissions(&shell, fs::Permissions::from_mode(0o4755)).unwrap();
+  assert_eq!(reason(run(&shell, &shells)), UntrustedReason::Setuid);
+}
+
+#[test]
+fn shells_file_without_strict_mode() {
+  let dir = tempfile::tempdir().unwrap();
+  let shells = dir.path().join("shells");
+  fs::write(&shells, "/bin/bash\n").unwrap();
+  common::sh()
+    .vars(&["PATH"])
+    .shells_file(&shells)
+    .run()
+    .unwrap();
+}
@@ -0,0 +1,104 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::{
+  ffi::CStr,
+  os::unix::process::CommandExt,
+  process::Command,
+  time::{Duration, Instant},
+};
+
+use fix_path_env::Error;
+
+#[test]
+fn hanging_shell() {
+  let start = Instant::now();
+  let err = common::sh()
+    .args(["-c", "sleep 30; eval \"$0\""])
+    .timeout(Duration::from_millis(500))
+    .run()
+    .unwrap_err();
+  assert!(matches!(err, Error::Timeout { .. }), "{err:?}");
+  assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn background_grandchild() {
+  // the `sleep` keeps stdout and stderr open after the shell exits
+  let start = Instant::now();
+  let report = common::sh()
+    .args(["-c", "sleep 20 & eval \"$0\""])
+    .vars(&["PATH"])
+    .timeout(Duration::from_secs(10))
+    .run()
+    .unwrap();
+  assert!(report.env().contains_key("PATH"));
+  assert!(
+    start.elapsed() < Duration::from_secs(1),
+    "{:?}",
+    start.elapsed()
+  );
+}
+
+/// Runs [`interactive_shell_in_terminal`] in a new session whose controlling terminal is a pty,
+/// like an app started from a terminal.
+#[test]
+fn interactive_shell_with_controlling_terminal() {
+  // SAFETY: plain libc calls on a file descriptor we own; `ptsname` is only called here.
+  let (master, tty) = unsafe {
+    let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+    assert!(master >= 0);
+    assert_eq!(libc::grantpt(master), 0);
+    assert_eq!(libc::unlockpt(master), 0);
+    let name = libc::ptsname(master);
+    assert!(!name.is_null());
+    (master, CStr::from_ptr(name).to_owned())
+  };
+
+  let mut command = Command::new(std::env::current_exe().unwrap());
+  command.args([
+    "--exact",
+    "interactive_shell_in_terminal",
+    "--ignored",
+    "--nocapture",
+  ]);
+  // SAFETY: the hook only calls async-signal-safe functions.
+  unsafe {
+    command.pre_exec(move || {
+      if libc::setsid() == -1 {
+        return Err(std::io::Error::last_os_error());
+      }
+      let fd = libc::open(tty.as_ptr(), libc::O_RDWR);
+      if fd == -1 || libc::ioctl(fd, libc::TIOCSCTTY as _, 0) == -1 {
+        return Err(std::io::Error::last_os_error());
+      }
+      Ok(())
+    });
+  }
+  let status = command.status().unwrap();
+  // SAFETY: the child exited, nothing uses the pty anymore.
+  unsafe { libc::close(master) };
+  assert!(status.success());
+}
+
+#[test]
+#[ignore = "run by interactive_shell_with_controlling_terminal"]
+fn interactive_shell_in_terminal() {
+  let home = tempfile::tempdir().unwrap();
+  let start = Instant::now();
+  // the default `-ilc` flags, where bash takes over the terminal for job control
+  let report = common::fixer()
+    .shell(common::require("bash"))
+    .env("HOME", home.path())
+    .vars(&["PATH"])
+    .timeout(Duration::from_secs(5))
+    .run()
+    .unwrap();
+  assert!(report.env().contains_key("PATH"));
+  assert!(start.elapsed() < Duration::from_secs(5));
+}
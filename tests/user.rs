@@ -0,0 +1,101 @@
+// Copyright 2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg(not(windows))]
+
+mod common;
+
+use std::ffi::OsStr;
+
+use common::id;
+use fix_path_env::{Error, Fixer, User};
+
+fn fixer(user: impl Into<User>) -> Fixer {
+  common::sh()
+    .user(user)
+    // records the id the probe runs as, `$0` being the probe command
+    .args([
+      "-c",
+      "FIX_PATH_ENV_UID=$(id -u) FIX_PATH_ENV_GROUPS=$(id -G) exec /bin/sh -c \"$0\"",
+    ])
+    .vars(&[
+      "HOME",
+      "USER",
+      "LOGNAME",
+      "SHELL",
+      "CARGO_MANIFEST_DIR",
+      "FIX_PATH_ENV_UID",
+      "FIX_PATH_ENV_GROUPS",
+    ])
+}
+
+fn groups(user: &str) -> String {
+  let output = std::process::Command::new("id")
+    .args(["-G", user])
+    .output()
+    .unwrap();
+  String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn unknown_user() {
+  assert!(matches!(
+    fixer("fix-path-env-unknown-user").run(),
+    Err(Error::UnknownUser(User::Name(_)))
+  ));
+}
+
+#[test]
+#[ignore = "requires root"]
+fn other_user() {
+  let home = std::env::var_os("HOME");
+  // `daemon` exists on most systems, with a home directory but no login shell
+  let report = fixer("daemon").run().unwrap();
+  let env = report.env();
+  assert_eq!(env.get("USER"), Some(OsStr::new("daemon")));
+  assert_eq!(env.get("LOGNAME"), Some(OsStr::new("daemon")));
+  assert_ne!(env.get("FIX_PATH_ENV_UID"), Some(OsStr::new("0")));
+  assert_ne!(env.get("HOME"), home.as_deref());
+  assert_eq!(std::env::var_os("HOME"), home);
+  // the login shell of `daemon` and none of our variables
+  assert_eq!(
+    env.get("SHELL"),
+    Some(common::login_shell("daemon").as_os_str())
+  );
+  assert_eq!(env.get("CARGO_MANIFEST_DIR"), None);
+  assert_eq!(
+    env.get("FIX_PATH_ENV_GROUPS"),
+    Some(OsStr::new(&groups("daemon")))
+  );
+}
+
+#[test]
+fn current_user_by_id() {
+  let uid = id("-u");
+  let report = fixer(uid.parse::<u32>().unwrap())
+    // the privilege policy does not apply to an explicit user, even root
+    .privilege_policy(fix_path_env::PrivilegePolicy::Refuse)
+    .run()
+    .unwrap();
+  assert_eq!(report.env().get("FIX_PATH_ENV_UID"), Some(OsStr::new(&uid)));
+  assert_eq!(report.env().get("USER"), Some(OsStr::new(&id("-un"))));
+}
+
+#[test]
+fn environment_untouched_by_default() {
+  // the default `Apply::Process` of a new fixer
+  let report = Fixer::new()
+    .shell("/bin/sh")
+    .args(["-c"])
+    .env("FIX_PATH_ENV_USER_ONLY", "value")
+    .vars(&["FIX_PATH_ENV_USER_ONLY"])
+    .user(id("-u").parse::<u32>().unwrap())
+    .run()
+    .unwrap();
+  assert_eq!(
+    report.env().get("FIX_PATH_ENV_USER_ONLY"),
+    Some(OsStr::new("value"))
+  );
+  assert_eq!(std::env::var_os("FIX_PATH_ENV_USER_ONLY"), None);
+}